@@ -1,53 +1,498 @@
-use log;
-use reqwest::{header::HeaderMap, Error, Response};
+use super::auth::{CredentialProvider, PatCredentials};
+use super::retry::RetryPolicy;
+use crate::config::Config;
+use crate::error::{ApiError, Error};
+use reqwest::{header::HeaderMap, Certificate, Method, Proxy, Response, Url};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Represents an API client for making HTTP requests.
+///
+/// The client owns a single `reqwest::Client` so connections and TLS sessions are
+/// reused across requests. Clones share the same connection pool.
 #[derive(Clone)]
 pub struct APIClient {
-    /// The database token used for authorization.
-    pub db_token: String,
+    /// Supplies the bearer token sent with every request.
+    pub credentials: Arc<dyn CredentialProvider>,
     /// The name of the workspace associated with the API client.
     pub workspace_name: String,
+    /// Base URL every API path is resolved against i.e. 'https://adb-12345678912345.11.azuredatabricks.net'.
+    /// May include a port and path prefix, such as 'http://127.0.0.1:8080/proxy'.
+    pub base_url: String,
+    /// The retry policy applied to every request.
+    pub retry_policy: RetryPolicy,
+    /// The long lived HTTP client used for every request.
+    http_client: reqwest::Client,
 }
 
 impl APIClient {
+    /// Creates an API client with the default HTTP configuration and retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `workspace_name` - The Databricks workspace name i.e. 'adb-12345678912345.11.azuredatabricks.net'
+    /// * `db_token` - The token used for authorization.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let api_client: APIClient = APIClient::new(workspace_name.clone(), db_token.clone());
+    /// ```
+    pub fn new(workspace_name: String, db_token: String) -> Self {
+        APIClientBuilder::new(workspace_name, db_token)
+            .build()
+            .expect("the default HTTP client configuration is valid")
+    }
+
+    /// Returns a builder to configure timeouts, proxy, TLS and retries.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let api_client: APIClient = APIClient::builder(workspace_name, db_token)
+    ///     .connect_timeout(Duration::from_secs(5))
+    ///     .proxy("http://proxy.internal:3128")
+    ///     .build()?;
+    /// ```
+    pub fn builder(workspace_name: String, db_token: String) -> APIClientBuilder {
+        APIClientBuilder::new(workspace_name, db_token)
+    }
+
+    /// Returns a builder that authenticates with the given credential provider instead of a personal access token.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let credentials = OAuthM2MCredentials::new("https://adb-12345678912345.11.azuredatabricks.net", client_id, client_secret);
+    ///  let api_client: APIClient = APIClient::builder_with_credentials(workspace_name, credentials).build()?;
+    /// ```
+    pub fn builder_with_credentials(
+        workspace_name: String,
+        credentials: impl CredentialProvider + 'static,
+    ) -> APIClientBuilder {
+        APIClientBuilder::new(workspace_name, String::new()).credentials(credentials)
+    }
+
+    /// Creates an API client from resolved connection settings.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let api_client: APIClient = APIClient::from_config(&Config::from_profile("dev")?)?;
+    /// ```
+    pub fn from_config(config: &Config) -> Result<Self, Error> {
+        APIClientBuilder::from_config(config)?.build()
+    }
+
+    /// Replaces the retry policy used by the client.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     /// Fetches data from the specified URL asynchronously.
     ///
+    /// Transient failures are retried according to the client's `RetryPolicy`.
+    ///
     /// # Arguments
     ///
     /// * `url` - The URL to fetch data from.
+    /// * `token` - Optional bearer token overriding the client's credentials.
     ///
     /// # Returns
     ///
-    /// A Result containing the HTTP response if successful, or an Error if an error occurs.
+    /// A Result containing the HTTP response if successful. Non 2xx responses are returned
+    /// as `Error::Api` carrying the status, `error_code`, `message` and request URL.
     pub async fn fetch(&self, url: &str, token: Option<&str>) -> Result<Response, Error> {
-        let client: reqwest::Client = reqwest::Client::new();
+        self.send(Method::GET, url, None, token).await
+    }
+
+    /// Sends a GET request to a workspace API path and deserializes the JSON response.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The API path i.e. '/api/2.1/unity-catalog/catalogs', or a full URL.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let catalog: Catalog = api_client.get("/api/2.1/unity-catalog/catalogs/main").await?;
+    /// ```
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        let response: Response = self.send(Method::GET, &self.url(path), None, None).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Sends a POST request with a JSON body and deserializes the JSON response. An empty
+    /// response body, i.e. `204 No Content`, deserializes into `()` or `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The API path, or a full URL.
+    /// * `body` - Request body serialized with serde.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let schema: Schema = api_client.post("/api/2.1/unity-catalog/schemas", &json!({"name": "sales", "catalog_name": "main"})).await?;
+    /// ```
+    pub async fn post<B, T>(&self, path: &str, body: &B) -> Result<T, Error>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.send_json(Method::POST, path, body).await
+    }
+
+    /// Sends a PATCH request with a JSON body and deserializes the JSON response. An empty
+    /// response body, i.e. `204 No Content`, deserializes into `()` or `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The API path, or a full URL.
+    /// * `body` - Request body serialized with serde.
+    pub async fn patch<B, T>(&self, path: &str, body: &B) -> Result<T, Error>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.send_json(Method::PATCH, path, body).await
+    }
+
+    /// Sends a PUT request with a JSON body and deserializes the JSON response. An empty
+    /// response body, i.e. `204 No Content`, deserializes into `()` or `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The API path, or a full URL.
+    /// * `body` - Request body serialized with serde.
+    pub async fn put<B, T>(&self, path: &str, body: &B) -> Result<T, Error>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.send_json(Method::PUT, path, body).await
+    }
+
+    /// Sends a DELETE request. Any response body is discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The API path, or a full URL.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  api_client.delete("/api/2.1/unity-catalog/tables/main.sales.orders").await?;
+    /// ```
+    pub async fn delete(&self, path: &str) -> Result<(), Error> {
+        self.send(Method::DELETE, &self.url(path), None, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Resolves an API path against the client's `base_url`. Full URLs are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let url: String = api_client.url("/api/2.1/unity-catalog/catalogs");
+    /// ```
+    pub fn url(&self, path: &str) -> String {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return path.to_string();
+        }
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
+    /// Resolves an API path like `url` and parses the result, so query parameters can be
+    /// appended with `Url::query_pairs_mut`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidUrl` if the resolved URL cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let mut url: Url = api_client.parse_url("/api/2.1/unity-catalog/schemas")?;
+    ///  url.query_pairs_mut().append_pair("catalog_name", "main");
+    /// ```
+    pub fn parse_url(&self, path: &str) -> Result<Url, Error> {
+        let url: String = self.url(path);
+        Url::parse(&url).map_err(|e| Error::InvalidUrl(format!("{}: {}", url, e)))
+    }
+
+    /// Serializes the body, sends the request and deserializes the JSON response.
+    async fn send_json<B, T>(&self, method: Method, path: &str, body: &B) -> Result<T, Error>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let payload: Vec<u8> = serde_json::to_vec(body)?;
+        let response: Response = self
+            .send(method, &self.url(path), Some(payload), None)
+            .await?;
+        let bytes = response.bytes().await?;
+        // some write endpoints answer with no content, which only `()` or an `Option` accepts
+        if bytes.is_empty() {
+            return Ok(serde_json::from_value(serde_json::Value::Null)?);
+        }
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Sends a request, retrying transient failures of idempotent requests according to the
+    /// client's `RetryPolicy`.
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<Vec<u8>>,
+        token: Option<&str>,
+    ) -> Result<Response, Error> {
+        let client: &reqwest::Client = &self.http_client;
         let mut headers: HeaderMap = HeaderMap::new();
         headers.insert("content-type", "application/json".parse().unwrap());
 
-        // Use the provided token if it exists, otherwise use the default db_token
-        let auth_token = token.unwrap_or(&self.db_token);
-        headers.insert(
-            "Authorization",
-            format!("Bearer {}", auth_token).parse().unwrap(),
-        );
-
-        let response: Response = client.get(url).headers(headers.clone()).send().await?;
-
-        // Check if the response status code is not 200
-        if !response.status().is_success() {
-            // Log an error message
-            let error_response: Response = client.get(url).headers(headers.clone()).send().await?;
-
-            let resp_text = error_response.text().await?;
-            log::error!(
-                "Request to {} failed with status code: {} - {}",
-                url,
-                response.status(),
-                resp_text
+        // writes may already have been applied when they fail, see `RetryPolicy`
+        let max_attempts: u32 = if self.retry_policy.is_retryable_method(&method) {
+            self.retry_policy.max_attempts
+        } else {
+            1
+        };
+        let mut attempt: u32 = 1;
+        loop {
+            // Use the provided token if it exists, otherwise ask the credential provider.
+            // Asking on every attempt lets providers refresh tokens that expired during retries.
+            let auth_token: String = match token {
+                Some(token) => token.to_string(),
+                None => self.credentials.token().await?,
+            };
+            headers.insert(
+                "Authorization",
+                format!("Bearer {}", auth_token).parse().map_err(|_| {
+                    Error::Credentials(
+                        "bearer token contains invalid header characters".to_string(),
+                    )
+                })?,
             );
+
+            let mut request = client.request(method.clone(), url).headers(headers.clone());
+            if let Some(payload) = &body {
+                request = request.body(payload.clone());
+            }
+
+            let response: Response = match request.send().await {
+                Ok(response) => response,
+                Err(e) if attempt < max_attempts && self.retry_policy.is_retryable_error(&e) => {
+                    let delay: Duration = self.retry_policy.delay(attempt, None);
+                    log::warn!(
+                        "{} request to {} failed ({}), retrying in {:?} (attempt {}/{})",
+                        method,
+                        url,
+                        e,
+                        delay,
+                        attempt,
+                        max_attempts
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if response.status().is_success() {
+                return Ok(response);
+            }
+
+            let status: u16 = response.status().as_u16();
+            if attempt < max_attempts && self.retry_policy.is_retryable_status(status) {
+                let delay: Duration = self.retry_policy.delay(attempt, Some(response.headers()));
+                log::warn!(
+                    "{} request to {} returned status {}, retrying in {:?} (attempt {}/{})",
+                    method,
+                    url,
+                    status,
+                    delay,
+                    attempt,
+                    max_attempts
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+
+            // Turn any non 2xx response into an API error using the body of this response
+            let body: String = response.text().await?;
+            let api_error: ApiError = ApiError::from_body(status, url, &body);
+            log::error!("{}", api_error);
+            return Err(Error::Api(api_error));
+        }
+    }
+}
+
+/// Builder for `APIClient`.
+pub struct APIClientBuilder {
+    workspace_name: String,
+    credentials: Arc<dyn CredentialProvider>,
+    base_url: Option<String>,
+    retry_policy: RetryPolicy,
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    user_agent: String,
+    proxy: Option<String>,
+    ca_bundle: Option<PathBuf>,
+    pool_max_idle_per_host: usize,
+}
+
+impl APIClientBuilder {
+    /// Creates a builder with the default settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `workspace_name` - The Databricks workspace name i.e. 'adb-12345678912345.11.azuredatabricks.net'
+    /// * `db_token` - The token used for authorization.
+    pub fn new(workspace_name: String, db_token: String) -> Self {
+        APIClientBuilder {
+            workspace_name,
+            credentials: Arc::new(PatCredentials::new(db_token)),
+            base_url: None,
+            retry_policy: RetryPolicy::default(),
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(60),
+            user_agent: format!("databricks-rust-catalog/{}", env!("CARGO_PKG_VERSION")),
+            proxy: None,
+            ca_bundle: None,
+            pool_max_idle_per_host: 16,
         }
+    }
+
+    /// Creates a builder from resolved connection settings, using the host as base URL and
+    /// the credential provider matching the configured authentication type.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Config` if the host or the credentials are missing.
+    pub fn from_config(config: &Config) -> Result<Self, Error> {
+        let mut builder: APIClientBuilder =
+            APIClientBuilder::new(config.workspace_name()?, String::new());
+        builder.credentials = config.credentials()?;
+        builder.base_url = Some(config.base_url()?);
+        Ok(builder)
+    }
+
+    /// Overrides the base URL API paths are resolved against. Defaults to `https://{workspace_name}`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - Scheme, host, optional port and optional path prefix i.e. 'http://127.0.0.1:8080'.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Replaces the personal access token with another credential provider.
+    pub fn credentials(mut self, credentials: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Arc::new(credentials);
+        self
+    }
+
+    /// Sets the retry policy applied to every request.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets the timeout for establishing a connection.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets the deadline for a whole request, from connecting until the response body has been
+    /// read. Defaults to 60 seconds.
+    ///
+    /// Every attempt of a retried request gets its own deadline. There is no separate timeout
+    /// between reads, so a slow but steady response is cut off by this deadline as well.
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Routes every request through the given HTTP(S) proxy i.e. 'http://proxy.internal:3128'.
+    pub fn proxy(mut self, proxy_url: &str) -> Self {
+        self.proxy = Some(proxy_url.to_string());
+        self
+    }
+
+    /// Trusts the certificates in the given PEM bundle in addition to the built in roots.
+    pub fn ca_bundle(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_bundle = Some(path.into());
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept per host.
+    pub fn pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = max_idle;
+        self
+    }
+
+    /// Builds the `APIClient`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Config` if the CA bundle cannot be read and `Error::Http` if the
+    /// proxy URL or certificates are invalid.
+    pub fn build(self) -> Result<APIClient, Error> {
+        let mut builder: reqwest::ClientBuilder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .user_agent(self.user_agent)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host);
+
+        if let Some(proxy_url) = &self.proxy {
+            builder = builder.proxy(Proxy::all(proxy_url)?);
+        }
+
+        if let Some(path) = &self.ca_bundle {
+            let pem: Vec<u8> = std::fs::read(path).map_err(|e| {
+                Error::Config(format!(
+                    "unable to read CA bundle {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            for certificate in Certificate::from_pem_bundle(&pem)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        let base_url: String = self
+            .base_url
+            .unwrap_or_else(|| format!("https://{}", self.workspace_name));
+
+        let http_client: reqwest::Client = builder.build()?;
+        self.credentials.use_http_client(&http_client);
 
-        Ok(response)
+        Ok(APIClient {
+            credentials: self.credentials,
+            workspace_name: self.workspace_name,
+            base_url,
+            retry_policy: self.retry_policy,
+            http_client,
+        })
     }
 }
@@ -0,0 +1,314 @@
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `prev_hash` of the first record of a log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Operation a principal attempted on a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditAction {
+    /// Reading the table, requires `SELECT`.
+    Read,
+    /// Writing to the table, requires `MODIFY`.
+    Write,
+}
+
+/// A permission decision on a table and, when allowed, what was transferred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    /// The principal the decision was made for.
+    pub principal: String,
+    /// The attempted operation.
+    pub action: AuditAction,
+    /// Fully qualified name of the table.
+    pub table: String,
+    /// True if the operation was allowed.
+    pub allowed: bool,
+    /// Version of the Delta table that was read or written, `None` when denied.
+    pub table_version: Option<i64>,
+    /// Number of bytes transferred, `None` when denied or unknown i.e. for lazy dataframes.
+    pub bytes: Option<u64>,
+}
+
+impl AuditEvent {
+    /// Creates an event without table version or bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let event: AuditEvent = AuditEvent::new("first.last@example.com", AuditAction::Read, "main.sales.orders", true)
+    ///     .table_version(12)
+    ///     .bytes(4096);
+    /// ```
+    pub fn new(principal: &str, action: AuditAction, table: &str, allowed: bool) -> Self {
+        AuditEvent {
+            principal: principal.to_string(),
+            action,
+            table: table.to_string(),
+            allowed,
+            table_version: None,
+            bytes: None,
+        }
+    }
+
+    /// Sets the version of the Delta table.
+    pub fn table_version(mut self, table_version: i64) -> Self {
+        self.table_version = Some(table_version);
+        self
+    }
+
+    /// Sets the number of bytes transferred.
+    pub fn bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+}
+
+/// One line of a JSON-lines audit log.
+///
+/// `hash` is the SHA-256 of the record serialized with an empty `hash`, and `prev_hash` is the
+/// `hash` of the previous record, so editing, inserting or removing a record breaks the chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position of the record in the log, starting at 1.
+    pub sequence: u64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    /// The principal the decision was made for.
+    pub principal: String,
+    /// The attempted operation.
+    pub action: AuditAction,
+    /// Fully qualified name of the table.
+    pub table: String,
+    /// True if the operation was allowed.
+    pub allowed: bool,
+    /// Version of the Delta table that was read or written.
+    pub table_version: Option<i64>,
+    /// Number of bytes transferred.
+    pub bytes: Option<u64>,
+    /// `hash` of the previous record, `GENESIS_HASH` for the first one.
+    pub prev_hash: String,
+    /// Hex encoded SHA-256 of this record.
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// Computes the hash of the record, ignoring its current `hash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Json` if the record cannot be serialized.
+    pub fn compute_hash(&self) -> Result<String, Error> {
+        let unhashed: AuditRecord = AuditRecord {
+            hash: String::new(),
+            ..self.clone()
+        };
+        let json: Vec<u8> = serde_json::to_vec(&unhashed)?;
+        Ok(hex::encode(Sha256::digest(&json)))
+    }
+}
+
+/// Destination of audit events.
+///
+/// Events are recorded before the result of an audited operation is returned, and a failure to
+/// record fails the operation, so no read or write goes unaudited.
+pub trait AuditSink: Send + Sync {
+    /// Durably records an event.
+    fn record(&self, event: AuditEvent) -> Result<(), Error>;
+}
+
+// lets a sink shared with other readers be passed where a sink is expected
+impl<T: AuditSink + ?Sized> AuditSink for Arc<T> {
+    fn record(&self, event: AuditEvent) -> Result<(), Error> {
+        (**self).record(event)
+    }
+}
+
+/// Audit sink appending hash chained `AuditRecord`s to a local JSON-lines file.
+///
+/// Every record is flushed to disk before `record` returns. The file must only be written by
+/// one sink at a time, use `verify_audit_log` to detect edits.
+pub struct JsonLinesAuditSink {
+    path: PathBuf,
+    chain: Mutex<ChainState>,
+}
+
+struct ChainState {
+    file: File,
+    sequence: u64,
+    last_hash: String,
+}
+
+impl JsonLinesAuditSink {
+    /// Opens the log, creating it if needed. An existing log is verified first and new records
+    /// continue its chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Audit` if the file cannot be read or created and
+    /// `Error::AuditTampered` if the existing log does not verify.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let sink: JsonLinesAuditSink = JsonLinesAuditSink::open("/var/log/databricks-rust/audit.jsonl")?;
+    /// let reader: DeltaLakeManager = DeltaLakeManager::builder().audit_sink(sink)/* ... */.build().await?;
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+        let summary: AuditLogSummary = if path.exists() {
+            verify_audit_log(path)?
+        } else {
+            AuditLogSummary {
+                records: 0,
+                last_hash: GENESIS_HASH.to_string(),
+            }
+        };
+        let file: File = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| Error::Audit(format!("unable to open {}: {}", path.display(), e)))?;
+
+        Ok(JsonLinesAuditSink {
+            path: path.to_path_buf(),
+            chain: Mutex::new(ChainState {
+                file,
+                sequence: summary.records,
+                last_hash: summary.last_hash,
+            }),
+        })
+    }
+
+    /// The path of the log.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AuditSink for JsonLinesAuditSink {
+    fn record(&self, event: AuditEvent) -> Result<(), Error> {
+        // a panic while holding the lock happens before the state is updated, so keep using it
+        let mut chain = self
+            .chain
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let timestamp_ms: u64 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let mut record: AuditRecord = AuditRecord {
+            sequence: chain.sequence + 1,
+            timestamp_ms,
+            principal: event.principal,
+            action: event.action,
+            table: event.table,
+            allowed: event.allowed,
+            table_version: event.table_version,
+            bytes: event.bytes,
+            prev_hash: chain.last_hash.clone(),
+            hash: String::new(),
+        };
+        record.hash = record.compute_hash()?;
+
+        let line: String = serde_json::to_string(&record)? + "\n";
+        chain
+            .file
+            .write_all(line.as_bytes())
+            .and_then(|_| chain.file.sync_data())
+            .map_err(|e| {
+                Error::Audit(format!("unable to write to {}: {}", self.path.display(), e))
+            })?;
+
+        chain.sequence = record.sequence;
+        chain.last_hash = record.hash;
+        Ok(())
+    }
+}
+
+/// Result of verifying an audit log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditLogSummary {
+    /// Number of records in the log.
+    pub records: u64,
+    /// `hash` of the last record, `GENESIS_HASH` for an empty log.
+    pub last_hash: String,
+}
+
+/// Verifies the hash chain of a JSON-lines audit log.
+///
+/// Each record must be unmodified, follow the previous record and carry the next sequence
+/// number. Truncating the end of the log leaves a valid chain, so keep the `last_hash` of the
+/// returned summary somewhere else to detect it.
+///
+/// # Arguments
+///
+/// * `path` - Path of the log written by `JsonLinesAuditSink`.
+///
+/// # Returns
+///
+/// * `AuditLogSummary` - The number of records and the hash of the last one.
+///
+/// # Errors
+///
+/// Returns `Error::Audit` if the file cannot be read and `Error::AuditTampered` with the first
+/// line that does not verify.
+///
+/// # Examples
+///
+/// ```ignore
+/// let summary: AuditLogSummary = verify_audit_log("/var/log/databricks-rust/audit.jsonl")?;
+/// assert_eq!(summary.last_hash, anchored_hash);
+/// ```
+pub fn verify_audit_log(path: impl AsRef<Path>) -> Result<AuditLogSummary, Error> {
+    let path: &Path = path.as_ref();
+    let file: File = File::open(path)
+        .map_err(|e| Error::Audit(format!("unable to read {}: {}", path.display(), e)))?;
+
+    let mut summary: AuditLogSummary = AuditLogSummary {
+        records: 0,
+        last_hash: GENESIS_HASH.to_string(),
+    };
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line: String =
+            line.map_err(|e| Error::Audit(format!("unable to read {}: {}", path.display(), e)))?;
+        let tampered = |reason: String| Error::AuditTampered {
+            line: index + 1,
+            reason,
+        };
+
+        let record: AuditRecord = serde_json::from_str(&line)
+            .map_err(|e| tampered(format!("not an audit record: {}", e)))?;
+        if record.sequence != summary.records + 1 {
+            return Err(tampered(format!(
+                "expected sequence {}, found {}",
+                summary.records + 1,
+                record.sequence
+            )));
+        }
+        if record.prev_hash != summary.last_hash {
+            return Err(tampered(
+                "prev_hash does not match the previous record".to_string(),
+            ));
+        }
+        if record.compute_hash()? != record.hash {
+            return Err(tampered("hash does not match the record".to_string()));
+        }
+        // the hash only covers known fields, so also reject extra fields and reformatting
+        if serde_json::to_string(&record)? != line {
+            return Err(tampered("the line is not as written".to_string()));
+        }
+
+        summary.records = record.sequence;
+        summary.last_hash = record.hash;
+    }
+    Ok(summary)
+}
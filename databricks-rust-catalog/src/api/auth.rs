@@ -0,0 +1,327 @@
+use crate::error::Error;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Resource id of the AzureDatabricks first party application, used as the token scope.
+const AZURE_DATABRICKS_SCOPE: &str = "2ff814a6-3304-4ab8-85cb-cd0e6f879c1d/.default";
+/// Default Azure AD authority for the public cloud.
+const AZURE_AUTHORITY_HOST: &str = "https://login.microsoftonline.com";
+/// Tokens are refreshed this long before they expire so in flight requests never carry a stale token.
+const REFRESH_LEEWAY: Duration = Duration::from_secs(60);
+
+/// Supplies the bearer token sent with every request of an `APIClient`.
+///
+/// The client calls `token` before each request, so implementations are expected to cache
+/// tokens and only contact their identity provider when the cached token is about to expire.
+#[async_trait]
+pub trait CredentialProvider: Send + Sync {
+    /// Returns a valid bearer token.
+    async fn token(&self) -> Result<String, Error>;
+
+    /// Short name of the authentication type i.e. 'pat' or 'oauth-m2m'.
+    fn auth_type(&self) -> &'static str;
+
+    /// Called by `APIClientBuilder::build` with the client's HTTP client, so providers that
+    /// request tokens over HTTP use the same proxy, CA bundle, timeouts and user agent.
+    fn use_http_client(&self, _http_client: &reqwest::Client) {}
+}
+
+// lets shared providers, such as the one resolved from a `Config`, be passed where a provider is expected
+#[async_trait]
+impl<T: CredentialProvider + ?Sized> CredentialProvider for Arc<T> {
+    async fn token(&self) -> Result<String, Error> {
+        (**self).token().await
+    }
+
+    fn auth_type(&self) -> &'static str {
+        (**self).auth_type()
+    }
+
+    fn use_http_client(&self, http_client: &reqwest::Client) {
+        (**self).use_http_client(http_client)
+    }
+}
+
+/// Personal access token authentication.
+pub struct PatCredentials {
+    token: String,
+}
+
+impl PatCredentials {
+    /// Creates a provider that always returns the given personal access token.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let credentials: PatCredentials = PatCredentials::new(db_token);
+    /// ```
+    pub fn new(token: String) -> Self {
+        PatCredentials { token }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for PatCredentials {
+    async fn token(&self) -> Result<String, Error> {
+        Ok(self.token.clone())
+    }
+
+    fn auth_type(&self) -> &'static str {
+        "pat"
+    }
+}
+
+/// OAuth machine-to-machine authentication for Databricks service principals.
+///
+/// Exchanges the service principal's client id and secret for a workspace token using the
+/// client credentials grant against `{workspace_url}/oidc/v1/token`.
+/// Refer to: https://docs.databricks.com/en/dev-tools/auth/oauth-m2m.html
+pub struct OAuthM2MCredentials {
+    flow: ClientCredentialsFlow,
+}
+
+impl OAuthM2MCredentials {
+    /// Creates an OAuth M2M provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `workspace_url` - The workspace URL i.e. 'https://adb-12345678912345.11.azuredatabricks.net'.
+    /// * `client_id` - The service principal's application id.
+    /// * `client_secret` - An OAuth secret of the service principal.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let credentials: OAuthM2MCredentials = OAuthM2MCredentials::new(&workspace_url, client_id, client_secret);
+    /// ```
+    pub fn new(workspace_url: &str, client_id: String, client_secret: String) -> Self {
+        let token_url: String = format!("{}/oidc/v1/token", workspace_url.trim_end_matches('/'));
+        OAuthM2MCredentials {
+            flow: ClientCredentialsFlow::new(
+                token_url,
+                client_id,
+                client_secret,
+                "all-apis".to_string(),
+                ClientAuthentication::Basic,
+            ),
+        }
+    }
+
+    /// Overrides the token endpoint, for example to use an account level endpoint.
+    pub fn with_token_url(mut self, token_url: &str) -> Self {
+        self.flow.token_url = token_url.to_string();
+        self
+    }
+
+    /// Overrides the requested scope. Defaults to 'all-apis'.
+    pub fn with_scope(mut self, scope: &str) -> Self {
+        self.flow.scope = scope.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for OAuthM2MCredentials {
+    async fn token(&self) -> Result<String, Error> {
+        self.flow.token().await
+    }
+
+    fn auth_type(&self) -> &'static str {
+        "oauth-m2m"
+    }
+
+    fn use_http_client(&self, http_client: &reqwest::Client) {
+        self.flow.use_http_client(http_client)
+    }
+}
+
+/// Azure AD client secret authentication for Azure service principals.
+///
+/// Requests a token for the AzureDatabricks application from
+/// `{authority_host}/{tenant_id}/oauth2/v2.0/token` using the client credentials grant.
+/// Refer to: https://learn.microsoft.com/en-us/azure/databricks/dev-tools/auth/azure-sp
+pub struct AzureServicePrincipalCredentials {
+    flow: ClientCredentialsFlow,
+    tenant_id: String,
+}
+
+impl AzureServicePrincipalCredentials {
+    /// Creates an Azure service principal provider for the public Azure cloud.
+    ///
+    /// # Arguments
+    ///
+    /// * `tenant_id` - The Azure AD tenant (directory) id.
+    /// * `client_id` - The application (client) id of the service principal.
+    /// * `client_secret` - A client secret of the service principal.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let credentials = AzureServicePrincipalCredentials::new(tenant_id, client_id, client_secret);
+    /// ```
+    pub fn new(tenant_id: String, client_id: String, client_secret: String) -> Self {
+        let token_url: String = azure_token_url(AZURE_AUTHORITY_HOST, &tenant_id);
+        AzureServicePrincipalCredentials {
+            flow: ClientCredentialsFlow::new(
+                token_url,
+                client_id,
+                client_secret,
+                AZURE_DATABRICKS_SCOPE.to_string(),
+                ClientAuthentication::Form,
+            ),
+            tenant_id,
+        }
+    }
+
+    /// Overrides the Azure AD authority i.e. 'https://login.microsoftonline.us' for Azure Government.
+    pub fn with_authority_host(mut self, authority_host: &str) -> Self {
+        self.flow.token_url = azure_token_url(authority_host, &self.tenant_id);
+        self
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for AzureServicePrincipalCredentials {
+    async fn token(&self) -> Result<String, Error> {
+        self.flow.token().await
+    }
+
+    fn auth_type(&self) -> &'static str {
+        "azure-client-secret"
+    }
+
+    fn use_http_client(&self, http_client: &reqwest::Client) {
+        self.flow.use_http_client(http_client)
+    }
+}
+
+fn azure_token_url(authority_host: &str, tenant_id: &str) -> String {
+    format!(
+        "{}/{}/oauth2/v2.0/token",
+        authority_host.trim_end_matches('/'),
+        tenant_id
+    )
+}
+
+/// How the client id and secret are sent to the token endpoint.
+enum ClientAuthentication {
+    /// HTTP basic authentication, used by the Databricks OIDC endpoint.
+    Basic,
+    /// `client_id` and `client_secret` form fields, used by Azure AD.
+    Form,
+}
+
+/// OAuth 2.0 client credentials grant with an in memory token cache.
+struct ClientCredentialsFlow {
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scope: String,
+    client_authentication: ClientAuthentication,
+    // replaced by the `APIClient` the provider is used with
+    http_client: RwLock<reqwest::Client>,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+// response of an OAuth token endpoint
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+impl ClientCredentialsFlow {
+    fn new(
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+        scope: String,
+        client_authentication: ClientAuthentication,
+    ) -> Self {
+        ClientCredentialsFlow {
+            token_url,
+            client_id,
+            client_secret,
+            scope,
+            client_authentication,
+            http_client: RwLock::new(
+                reqwest::Client::builder()
+                    .timeout(Duration::from_secs(30))
+                    .build()
+                    .expect("the default HTTP client configuration is valid"),
+            ),
+            cached: Mutex::new(None),
+        }
+    }
+
+    fn use_http_client(&self, http_client: &reqwest::Client) {
+        *self
+            .http_client
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = http_client.clone();
+    }
+
+    /// Returns the cached token, requesting a new one if it is missing or about to expire.
+    /// The lock is held during the refresh so concurrent callers share a single token request.
+    async fn token(&self) -> Result<String, Error> {
+        let mut cached = self.cached.lock().await;
+        if let Some(token) = cached.as_ref() {
+            if Instant::now() + REFRESH_LEEWAY < token.expires_at {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        let token: CachedToken = self.request_token().await?;
+        let access_token: String = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    async fn request_token(&self) -> Result<CachedToken, Error> {
+        log::debug!("Requesting OAuth token from {}", self.token_url);
+        let mut form: Vec<(&str, &str)> = vec![
+            ("grant_type", "client_credentials"),
+            ("scope", self.scope.as_str()),
+        ];
+        let http_client: reqwest::Client = self
+            .http_client
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let mut request = http_client.post(&self.token_url);
+        match self.client_authentication {
+            ClientAuthentication::Basic => {
+                request = request.basic_auth(&self.client_id, Some(&self.client_secret));
+            }
+            ClientAuthentication::Form => {
+                form.push(("client_id", self.client_id.as_str()));
+                form.push(("client_secret", self.client_secret.as_str()));
+            }
+        }
+
+        let response: reqwest::Response = request.form(&form).send().await?;
+        let status: u16 = response.status().as_u16();
+        if !response.status().is_success() {
+            let body: String = response.text().await.unwrap_or_default();
+            return Err(Error::AuthenticationFailed(format!(
+                "token request to {} failed with status {}: {}",
+                self.token_url, status, body
+            )));
+        }
+
+        let token: TokenResponse = response.json().await?;
+        let expires_in: Duration = Duration::from_secs(token.expires_in.unwrap_or(3600));
+        Ok(CachedToken {
+            access_token: token.access_token,
+            expires_at: Instant::now() + expires_in,
+        })
+    }
+}
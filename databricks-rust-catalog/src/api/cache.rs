@@ -0,0 +1,227 @@
+use super::api_client::APIClient;
+use super::evaluator::{Decision, Operation};
+use super::permissions::{self, Privilege, SecurableType};
+use crate::error::Error;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// In-process cache of permission decisions keyed by principal, privilege and securable.
+///
+/// Evaluating a decision resolves the principal's groups and fetches the owner and effective
+/// permissions of the object and its parents, which takes several requests. Cached decisions,
+/// whether allowed or denied, are reused until their TTL expires, so grants and revocations
+/// take up to one TTL to be noticed. Use `invalidate` after changing grants to see them
+/// immediately. Failed evaluations are never cached.
+pub struct PermissionCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, CachedDecision>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    // principals and Unity Catalog names are case insensitive
+    principal: String,
+    privilege: Privilege,
+    securable_type: SecurableType,
+    full_name: String,
+}
+
+struct CachedDecision {
+    decision: Decision,
+    expires_at: Instant,
+}
+
+impl Default for PermissionCache {
+    fn default() -> Self {
+        PermissionCache::new(PermissionCache::DEFAULT_TTL)
+    }
+}
+
+impl PermissionCache {
+    /// How long decisions are cached unless configured otherwise.
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+    /// Creates an empty cache. A TTL of zero disables caching.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let cache: PermissionCache = PermissionCache::new(Duration::from_secs(300));
+    /// let allowed: bool = cache.check(api_client, "first.last@example.com", Privilege::Select, SecurableType::Table, "main.sales.orders").await?;
+    /// ```
+    pub fn new(ttl: Duration) -> Self {
+        PermissionCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How long decisions are cached.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the cached decision or evaluates it with `permissions::evaluate_access`.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_client` - API client object for making HTTP requests on a cache miss.
+    /// * `principal` - The principal user for which permissions are being checked.
+    /// * `privilege` - The privilege to check.
+    /// * `securable_type` - Type of securable object (e.g., Table, Volume, Function).
+    /// * `full_name` - Fully qualified name of the object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the decision is not cached and evaluating it fails.
+    pub async fn evaluate(
+        &self,
+        api_client: APIClient,
+        principal: &str,
+        privilege: Privilege,
+        securable_type: SecurableType,
+        full_name: &str,
+    ) -> Result<Decision, Error> {
+        let key: CacheKey = CacheKey {
+            principal: principal.to_lowercase(),
+            privilege,
+            securable_type: securable_type.clone(),
+            full_name: full_name.to_lowercase(),
+        };
+        if let Some(decision) = self.get(&key) {
+            log::info!(
+                "Permission cache hit: {} {} on {} {}.",
+                principal,
+                privilege,
+                securable_type,
+                full_name
+            );
+            return Ok(decision);
+        }
+        log::info!(
+            "Permission cache miss: {} {} on {} {}.",
+            principal,
+            privilege,
+            securable_type,
+            full_name
+        );
+
+        let decision: Decision = permissions::evaluate_access(
+            api_client,
+            securable_type,
+            full_name,
+            principal,
+            Operation::Privilege(privilege),
+        )
+        .await?;
+        self.insert(key, decision.clone());
+        Ok(decision)
+    }
+
+    /// Returns true if the principal holds the privilege, using the cached decision if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the decision is not cached and evaluating it fails.
+    pub async fn check(
+        &self,
+        api_client: APIClient,
+        principal: &str,
+        privilege: Privilege,
+        securable_type: SecurableType,
+        full_name: &str,
+    ) -> Result<bool, Error> {
+        let decision: Decision = self
+            .evaluate(api_client, principal, privilege, securable_type, full_name)
+            .await?;
+        Ok(decision.allowed)
+    }
+
+    /// Removes the decisions on an object and on every object inside it, for all principals.
+    /// Invalidating `main.sales` removes decisions on `main.sales` and `main.sales.orders`.
+    pub fn invalidate(&self, full_name: &str) {
+        let full_name: String = full_name.to_lowercase();
+        let prefix: String = format!("{}.", full_name);
+        let removed: usize = self
+            .remove_where(|key| key.full_name == full_name || key.full_name.starts_with(&prefix));
+        log::info!(
+            "Invalidated {} cached permission decisions on {}.",
+            removed,
+            full_name
+        );
+    }
+
+    /// Removes every decision for a principal, i.e. after its group memberships changed.
+    pub fn invalidate_principal(&self, principal: &str) {
+        let lowercase: String = principal.to_lowercase();
+        let removed: usize = self.remove_where(|key| key.principal == lowercase);
+        log::info!(
+            "Invalidated {} cached permission decisions for {}.",
+            removed,
+            principal
+        );
+    }
+
+    /// Removes every decision.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the number of cached decisions that have not expired.
+    pub fn len(&self) -> usize {
+        let now: Instant = Instant::now();
+        self.lock()
+            .values()
+            .filter(|entry| entry.expires_at > now)
+            .count()
+    }
+
+    /// Returns true if no decision is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<Decision> {
+        let mut entries = self.lock();
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.decision.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: CacheKey, decision: Decision) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let now: Instant = Instant::now();
+        let mut entries = self.lock();
+        // drop expired decisions so the cache does not grow with every object ever checked
+        entries.retain(|_, entry| entry.expires_at > now);
+        entries.insert(
+            key,
+            CachedDecision {
+                decision,
+                expires_at: now + self.ttl,
+            },
+        );
+    }
+
+    fn remove_where(&self, matches: impl Fn(&CacheKey) -> bool) -> usize {
+        let mut entries = self.lock();
+        let before: usize = entries.len();
+        entries.retain(|key, _| !matches(key));
+        before - entries.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<CacheKey, CachedDecision>> {
+        // a panic while holding the lock cannot leave the map inconsistent, so keep using it
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -1,99 +1,153 @@
-use super::api_client::APIClient;
+use super::api_client::{APIClient, APIClientBuilder};
+use super::audit::{AuditAction, AuditEvent, AuditSink};
+use super::auth::{CredentialProvider, PatCredentials};
+use super::cache::PermissionCache;
 use super::metastore::*;
-use super::permissions;
+use super::permissions::{self, Privilege, SecurableType, User};
+use crate::config::Config;
+use crate::error::Error;
 
 use deltalake::{
-    azure::register_handlers, datafusion::prelude::DataFrame as DatafusionDataFrame,
-    datafusion::prelude::*, open_table_with_storage_options, DeltaTable, ObjectStore, Path,
+    arrow::record_batch::RecordBatch, azure::register_handlers,
+    datafusion::prelude::DataFrame as DatafusionDataFrame, datafusion::prelude::*, kernel::Action,
+    logstore::get_actions, open_table_with_storage_options, DeltaOps, DeltaTable, ObjectStore,
+    Path,
 };
 use magic_crypt::MagicCryptTrait;
 use polars::prelude::{DataFrame as PolarsDataFrame, *};
 use reqwest::Response;
 
 use std::collections::HashMap;
-use std::convert::TryFrom;
 use std::env;
-use std::error::Error;
 use std::io::Cursor;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
-use futures;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use magic_crypt::new_magic_crypt;
 use serde::Deserialize;
 
 pub struct DeltaLakeManager {
-    storage_credentials: AzureDataLakeGen2Options,
+    storage_options: HashMap<String, String>,
     api_client: APIClient,
     metastore_client: Client,
-    principal: String,
+    user: User,
+    max_concurrent_reads: usize,
+    permission_cache: Arc<PermissionCache>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
 }
 impl DeltaLakeManager {
-    /// Creates the delta lake reader struct
+    /// Creates the delta lake reader struct using a personal access token.
+    /// The storage credential service is read from the `STORAGE_URL`, `STORAGE_PORT` and
+    /// `STORAGE_TOKEN` environment variables, use `DeltaLakeManager::builder` to pass it explicitly.
     ///
     /// # Arguments
     ///
-    /// * `storage_credentials` - The credentials used to authenticate against azure storage
-    /// * `api_client` - API Client to validate user permissions against unity catalog
-    /// * `metastore_client` - Metastore Client object to interact with Unity Catalog APIs for data objects.
     /// * `principal` - The active user's username.
+    /// * `db_token` - The token used to authenticate against Unity Catalog.
+    /// * `workspace_name` - The Databricks workspace name i.e. 'adb-12345678912345.11.azuredatabricks.net'
     ///
     /// # Examples
     ///
     /// ```ignore
-    ///  let reader: DeltaLakeManager = DeltaLakeManager::new(principal, db_token, workspace_name).await;
+    ///  let reader: DeltaLakeManager = DeltaLakeManager::new(principal, db_token, workspace_name).await?;
     /// ```
     pub async fn new(
         principal: String,
         db_token: String,
         workspace_name: String,
-    ) -> Result<Self, Box<dyn Error>> {
-        let api_client: APIClient = APIClient {
-            db_token: db_token.clone(),
-            workspace_name: workspace_name.clone(),
-        };
+    ) -> Result<Self, Error> {
+        DeltaLakeManager::builder()
+            .principal(&principal)
+            .workspace(&workspace_name)
+            .credentials(PatCredentials::new(db_token))
+            .credential_service_from_env()?
+            .build()
+            .await
+    }
 
-        let storage_url: String = env::var("STORAGE_URL").expect("STORAGE_URL not set");
-        let storage_port: String = env::var("STORAGE_PORT").expect("STORAGE_PORT not set");
-        let bearer_token = "test";
-        let storage_client = api_client.clone();
-        let storage_response: Response = storage_client
-            .fetch(
-                &format!("{}:{}/api/storage_credentials", &storage_url, &storage_port),
-                Some(&bearer_token),
-            )
-            .await?;
-        let storage_json: AzureDataLakeGen2Options = storage_response.json().await?;
+    /// Creates the delta lake reader from resolved connection settings, such as a `~/.databrickscfg` profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Settings providing the host, credentials, `user_name` and storage credential service.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let reader: DeltaLakeManager = DeltaLakeManager::from_config(&Config::load()?).await?;
+    /// ```
+    pub async fn from_config(config: &Config) -> Result<Self, Error> {
+        DeltaLakeManagerBuilder::from_config(config)?.build().await
+    }
 
-        let storage_credentials: AzureDataLakeGen2Options = AzureDataLakeGen2Options::new(
-            decrypt_strings(&storage_json.azure_storage_account_name, &bearer_token),
-            decrypt_strings(&storage_json.azure_client_id, &bearer_token),
-            decrypt_strings(&storage_json.azure_client_secret, &bearer_token),
-            decrypt_strings(&storage_json.azure_tenant_id, &bearer_token),
-        );
+    /// Returns a builder to configure the reader explicitly, without reading environment variables.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let reader: DeltaLakeManager = DeltaLakeManager::builder()
+    ///     .principal("first.last@example.com")
+    ///     .workspace("adb-12345678912345.11.azuredatabricks.net")
+    ///     .credentials(OAuthM2MCredentials::new(&workspace_url, client_id, client_secret))
+    ///     .credential_service("https://storage-credentials.internal:8000", &service_token)
+    ///     .max_concurrent_reads(4)
+    ///     .build()
+    ///     .await?;
+    /// ```
+    pub fn builder() -> DeltaLakeManagerBuilder {
+        DeltaLakeManagerBuilder::new()
+    }
 
-        let _authorized: bool =
-            permissions::authenticate_user(api_client.clone(), &principal).await?;
+    /// The principal whose permissions are checked, as verified against the token.
+    pub fn principal(&self) -> &str {
+        self.user.principal()
+    }
 
-        let metastore_client: Client = Client::new(workspace_name.clone(), db_token.clone());
+    /// The user or service principal the token belongs to.
+    pub fn user(&self) -> &User {
+        &self.user
+    }
 
-        let reader: DeltaLakeManager = DeltaLakeManager {
-            storage_credentials,
-            api_client,
-            metastore_client,
-            principal,
-        };
+    /// The options used to open tables in object storage.
+    pub fn storage_options(&self) -> &HashMap<String, String> {
+        &self.storage_options
+    }
 
-        // Call the register_handlers function
-        register_handlers(None);
+    /// The cache of permission decisions, i.e. to invalidate decisions after changing grants.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// permissions::update_permissions(api_client, SecurableType::Table, "main.sales.orders", &changes).await?;
+    /// reader.permission_cache().invalidate("main.sales.orders");
+    /// ```
+    pub fn permission_cache(&self) -> &PermissionCache {
+        &self.permission_cache
+    }
 
-        if _authorized {
-            return Ok(reader);
-        } else {
-            return Err(Box::<dyn Error>::from("Authorization Failed."));
+    /// Records an event if an audit sink is configured.
+    fn audit(&self, event: AuditEvent) -> Result<(), Error> {
+        match &self.audit_sink {
+            Some(sink) => sink.record(event),
+            None => Ok(()),
         }
     }
 
+    /// Checks a privilege of the principal on a table, reusing cached decisions.
+    async fn check_table(&self, privilege: Privilege, table_name: &str) -> Result<bool, Error> {
+        self.permission_cache
+            .check(
+                self.api_client.clone(),
+                self.principal(),
+                privilege,
+                SecurableType::Table,
+                table_name,
+            )
+            .await
+    }
+
     /// If the user has permission to read the table, then this function returns a datafusion dataframe.
     ///
     /// # Arguments
@@ -109,30 +163,40 @@ impl DeltaLakeManager {
     pub async fn read_delta_table_as_datafusion(
         &self,
         table_name: &str,
-    ) -> Result<DatafusionDataFrame, Box<dyn Error>> {
+    ) -> Result<DatafusionDataFrame, Error> {
         let table_path: String = self
             .metastore_client
             .get_table(table_name)
             .await?
             .storage_location
-            .ok_or("Table Location Not Found.")?;
-        if !permissions::can_read(self.api_client.clone(), &table_name, &self.principal).await? {
+            .ok_or_else(|| Error::MissingStorageLocation(table_name.to_string()))?;
+        if !self.check_table(Privilege::Select, table_name).await? {
             log::error!("Permissions on Object {} Denied.", table_name);
-            return Err(Box::<dyn Error>::from("Permission Denied."));
+            self.audit(AuditEvent::new(
+                self.principal(),
+                AuditAction::Read,
+                table_name,
+                false,
+            ))?;
+            Err(Error::PermissionDenied(table_name.to_string()))
         } else {
             log::info!("Validated Permissions on Object: {}", table_name);
 
             log::info!("Reading Table: {}", table_path);
             let table: DeltaTable =
-                open_table_with_storage_options(table_path, self.storage_credentials.to_hash_map())
-                    .await?;
+                open_table_with_storage_options(table_path, self.storage_options.clone()).await?;
+            // the dataframe is lazy, so the bytes read are not known here
+            self.audit(
+                AuditEvent::new(self.principal(), AuditAction::Read, table_name, true)
+                    .table_version(table.version()),
+            )?;
 
             let ctx: SessionContext = SessionContext::new();
 
             ctx.register_table("loadtable", Arc::new(table))?;
 
             let df: DatafusionDataFrame = ctx.sql("SELECT * FROM loadtable").await?;
-            return Ok(df);
+            Ok(df)
         }
     }
 
@@ -140,46 +204,35 @@ impl DeltaLakeManager {
     ///
     /// # Arguments
     ///
-    /// * `table_path` - The path to the table in cloud storage
+    /// * `table` - The opened table
     ///
     /// # Examples
     ///
     /// ```ignore
-    /// let table_path: String = self.metastore_client.get_table(table_name).await?.storage_location.ok_or("Table Location Not Found.")?;
-    /// let table_bytes = self.parallel_read_table_as_bytes(&table_path).await?;
+    /// let table: DeltaTable = open_table_with_storage_options(table_path, self.storage_options.clone()).await?;
+    /// let table_bytes = self.parallel_read_table_as_bytes(&table).await?;
     /// ```
-    async fn parallel_read_table_as_bytes(
-        &self,
-        table_path: &str,
-    ) -> Result<Vec<Bytes>, Box<dyn Error>> {
-        log::info!("Reading Table: {}", table_path);
-        let table: DeltaTable =
-            open_table_with_storage_options(table_path, self.storage_credentials.to_hash_map())
-                .await?;
-
+    async fn parallel_read_table_as_bytes(&self, table: &DeltaTable) -> Result<Vec<Bytes>, Error> {
         let files: Vec<String> = table.get_file_uris()?.collect();
         let object_store: Arc<dyn ObjectStore> = table.object_store();
 
-        let futures: Vec<_> = files
-            .into_iter()
+        // files are downloaded concurrently, at most `max_concurrent_reads` at a time
+        let table_bytes: Vec<Bytes> = stream::iter(files)
             .map(|file| {
                 let object_store = Arc::clone(&object_store);
                 async move {
                     log::info!("Loading file: {}", file);
                     let parts: Vec<&str> = file.split('/').collect();
-                    let file_name: &str = parts.last().ok_or("Error Parsing File URIs")?;
-                    let file_path: Path = Path::try_from(file_name.to_string())?;
+                    let file_name: &str = parts.last().copied().unwrap_or_default();
+                    let file_path: Path = Path::from(file_name);
                     let result = object_store.get(&file_path).await?;
                     let bytes = result.bytes().await?;
-                    Ok::<Bytes, Box<dyn Error>>(bytes)
+                    Ok::<Bytes, Error>(bytes)
                 }
             })
-            .collect();
-
-        let table_bytes: Vec<Bytes> = futures::future::join_all(futures)
-            .await
-            .into_iter()
-            .collect::<Result<Vec<_>, _>>()?;
+            .buffered(self.max_concurrent_reads)
+            .try_collect()
+            .await?;
 
         Ok(table_bytes)
     }
@@ -188,21 +241,16 @@ impl DeltaLakeManager {
     ///
     /// # Arguments
     ///
-    /// * `table_path` - The path to the table in cloud storage
+    /// * `table` - The opened table
     ///
     /// # Examples
     ///
     /// ```ignore
-    /// let table_path: String = self.metastore_client.get_table(table_name).await?.storage_location.ok_or("Table Location Not Found.")?;
-    /// let table_bytes = self.read_table_as_bytes(&table_path).await?;
+    /// let table: DeltaTable = open_table_with_storage_options(table_path, self.storage_options.clone()).await?;
+    /// let table_bytes = self.read_table_as_bytes(&table).await?;
     /// ```
-    async fn read_table_as_bytes(&self, table_path: &str) -> Result<Vec<Bytes>, Box<dyn Error>> {
+    async fn read_table_as_bytes(&self, table: &DeltaTable) -> Result<Vec<Bytes>, Error> {
         // return bytes
-        log::info!("Reading Table: {}", table_path);
-        let table: DeltaTable =
-            open_table_with_storage_options(table_path, self.storage_credentials.to_hash_map())
-                .await?;
-
         let mut table_bytes: Vec<Bytes> = Vec::default();
 
         // get the files and storage object
@@ -216,7 +264,7 @@ impl DeltaLakeManager {
             log::info!("Loading file: {}", file);
             let parts: Vec<&str> = file.split('/').collect();
             let file_name: &str = parts[parts.len() - 1];
-            let file_path: Path = Path::try_from(format!("{}", file_name))?;
+            let file_path: Path = Path::from(file_name);
             let result: deltalake::storage::GetResult = object_store.get(&file_path).await?;
             let bytes: Bytes = result.bytes().await?;
             table_bytes.push(bytes);
@@ -241,122 +289,141 @@ impl DeltaLakeManager {
         &self,
         table_name: &str,
         parallel_read: bool,
-    ) -> Result<PolarsDataFrame, Box<dyn Error>> {
-        // create empty DF - we will replace it later with the if/else
+    ) -> Result<PolarsDataFrame, Error> {
         let table_path: String = self
             .metastore_client
             .get_table(table_name)
             .await?
             .storage_location
-            .ok_or("Table Location Not Found.")?;
-        let mut df: PolarsDataFrame = PolarsDataFrame::default();
-        let mut _table_bytes: Vec<Bytes> = Vec::default();
+            .ok_or_else(|| Error::MissingStorageLocation(table_name.to_string()))?;
 
-        if !permissions::can_read(self.api_client.clone(), &table_name, &self.principal).await? {
+        if !self.check_table(Privilege::Select, table_name).await? {
             log::info!("Permissions on Object {} Denied.", table_name);
-            return Ok(df);
+            self.audit(AuditEvent::new(
+                self.principal(),
+                AuditAction::Read,
+                table_name,
+                false,
+            ))?;
+            return Err(Error::PermissionDenied(table_name.to_string()));
+        }
+
+        log::info!("Validated Permissions on Object: {}", table_name);
+        log::info!("Reading Table: {}", table_path);
+        let table: DeltaTable =
+            open_table_with_storage_options(&table_path, self.storage_options.clone()).await?;
+        // get the table as a vector of bytes each index is a parquet file
+        let table_bytes: Vec<Bytes> = if parallel_read {
+            log::info!("Parallel reading table.");
+            self.parallel_read_table_as_bytes(&table).await?
         } else {
-            log::info!("Validated Permissions on Object: {}", table_name);
-            // get the table as a vector of bytes each index is a parquet file
-            if parallel_read {
-                log::info!("Parallel reading table.");
-                _table_bytes = self.parallel_read_table_as_bytes(&table_path).await?;
-            } else {
-                log::info!("Seirially readin table.");
-                _table_bytes = self.read_table_as_bytes(&table_path).await?;
-            }
+            log::info!("Seirially readin table.");
+            self.read_table_as_bytes(&table).await?
+        };
+        let bytes: u64 = table_bytes.iter().map(|b| b.len() as u64).sum();
 
-            // foreach file we need only the file name
-            // load the bytes into a polars dataframe
-            for b in _table_bytes {
-                let cursor: Cursor<Bytes> = Cursor::new(b);
-                let new_df: PolarsDataFrame = ParquetReader::new(cursor).finish()?;
-
-                if df.is_empty() {
-                    df = new_df.clone();
-                } else {
-                    df = match df.vstack(&new_df) {
-                        Ok(stacked_df) => stacked_df,
-                        Err(e) => {
-                            // Handle the error if the vertical stack operation fails
-                            log::error!("Error stacking DataFrames: {}", e);
-                            df // Return the original DataFrame if the operation fails
-                        }
-                    };
-                }
+        // foreach file we need only the file name
+        // load the bytes into a polars dataframe
+        let mut df: PolarsDataFrame = PolarsDataFrame::default();
+        for b in table_bytes {
+            let cursor: Cursor<Bytes> = Cursor::new(b);
+            let new_df: PolarsDataFrame = ParquetReader::new(cursor).finish()?;
+
+            if df.is_empty() {
+                df = new_df;
+            } else {
+                df = df.vstack(&new_df)?;
             }
         }
+        self.audit(
+            AuditEvent::new(self.principal(), AuditAction::Read, table_name, true)
+                .table_version(table.version())
+                .bytes(bytes),
+        )?;
         Ok(df)
     }
 
-    pub async fn write_polars_to_delta_table(
-        &self,
-        table_name: &str,
-    ) -> Result<(), Box<dyn Error>> {
+    pub async fn write_polars_to_delta_table(&self, table_name: &str) -> Result<(), Error> {
         // create empty DF - we will replace it later with the if/else
         let _table_path: String = self
             .metastore_client
             .get_table(table_name)
             .await?
             .storage_location
-            .ok_or("Table Location Not Found.")?;
+            .ok_or_else(|| Error::MissingStorageLocation(table_name.to_string()))?;
 
         // INCOMPLETE
 
         Ok(())
     }
 
-    /// If the user has permission to read the table, then this function returns a datafusion dataframe.
+    /// If the user has permission to modify the table, then this function appends the rows of a
+    /// datafusion dataframe to it. The write is audited once it has been committed, with the size
+    /// of the files the commit added.
     ///
     /// # Arguments
     ///
     /// * `table_name` - The fully qualified table name
+    /// * `df` - The rows to append
     ///
     /// # Examples
     ///
     /// ```ignore
     /// let table_name: &str = "my_catalog.my_schema.my_table";
-    /// let df = reader.read_delta_table_as_datafusion(table_path).await;
+    /// writer.write_datafusion_to_delta(table_name, df).await?;
     /// ```
     pub async fn write_datafusion_to_delta(
         &self,
         table_name: &str,
         df: DatafusionDataFrame,
-    ) -> Result<(), Box<dyn Error>> {
+    ) -> Result<(), Error> {
         let table_metadata: Table = self.metastore_client.get_table(table_name).await?;
 
-        if !permissions::can_write(self.api_client.clone(), &table_name, &self.principal).await? {
+        if !self.check_table(Privilege::Modify, table_name).await? {
             log::error!("Permissions on Object {} Denied.", table_name);
-            return Err(Box::<dyn Error>::from("Permission Denied."));
-        } else {
-            log::info!("Validated Permissions on Object: {}", table_name);
-
-            log::info!("Reading Table: {}", table_metadata.full_name);
-            let _table: DeltaTable = open_table_with_storage_options(
-                table_metadata
-                    .storage_location
-                    .ok_or("Table Location Not Found.")?,
-                self.storage_credentials.to_hash_map(),
-            )
-            .await?;
-
-            // let record_batches = df.collect().await?;
-            // // get json rows
-            // let json_rows = deltalake::datafusion::arrow::json::writer::record_batches_to_json_rows(&record_batches[..])?.read_to_string();
-
-            // writer
-            // .write(
-            //     json_rows.lines()
-            //         .map(|line| serde_json::from_str(line)?)
-            //         .collect(),
-            // )
-            // .await?;
+            self.audit(AuditEvent::new(
+                self.principal(),
+                AuditAction::Write,
+                table_name,
+                false,
+            ))?;
+            return Err(Error::PermissionDenied(table_name.to_string()));
+        }
 
-            // // Commit the changes to the table.
-            // writer.flush_and_commit(&mut table).await?;
+        log::info!("Validated Permissions on Object: {}", table_name);
+        log::info!("Writing Table: {}", table_metadata.full_name);
+        let table: DeltaTable = open_table_with_storage_options(
+            table_metadata
+                .storage_location
+                .ok_or_else(|| Error::MissingStorageLocation(table_name.to_string()))?,
+            self.storage_options.clone(),
+        )
+        .await?;
+        let batches: Vec<RecordBatch> = df.collect().await?;
+        let table: DeltaTable = DeltaOps(table).write(batches).await?;
+        let bytes: u64 = Self::added_bytes(&table).await?;
+        self.audit(
+            AuditEvent::new(self.principal(), AuditAction::Write, table_name, true)
+                .table_version(table.version())
+                .bytes(bytes),
+        )?;
+        Ok(())
+    }
 
-            return Ok(());
-        }
+    /// Sums the size of the files added by the latest commit of a table.
+    async fn added_bytes(table: &DeltaTable) -> Result<u64, Error> {
+        let version: i64 = table.version();
+        let Some(commit) = table.log_store().read_commit_entry(version).await? else {
+            return Ok(0);
+        };
+        let actions: Vec<Action> = get_actions(version, commit).await?;
+        Ok(actions
+            .iter()
+            .map(|action| match action {
+                Action::Add(add) => add.size as u64,
+                _ => 0,
+            })
+            .sum())
     }
 
     // async fn polars_to_arrow(df: &polars::prelude::DataFrame) -> Result<RecordBatch, PolarsError> {
@@ -444,8 +511,257 @@ impl AzureDataLakeGen2Options {
     }
 }
 
-fn decrypt_strings(string_value: &str, key: &str) -> String {
+/// Builder for `DeltaLakeManager`.
+///
+/// Storage options are obtained from the storage credential service when one is configured,
+/// and options set with `storage_option(s)` are applied on top of them. Either source alone
+/// is enough, so embedding services can pass storage credentials they already hold.
+pub struct DeltaLakeManagerBuilder {
+    principal: Option<String>,
+    workspace_name: Option<String>,
+    base_url: Option<String>,
+    credentials: Option<Arc<dyn CredentialProvider>>,
+    api_client: Option<APIClient>,
+    credential_service_url: Option<String>,
+    credential_service_token: Option<String>,
+    storage_options: HashMap<String, String>,
+    max_concurrent_reads: usize,
+    permission_cache: Option<Arc<PermissionCache>>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+}
+
+impl Default for DeltaLakeManagerBuilder {
+    fn default() -> Self {
+        DeltaLakeManagerBuilder::new()
+    }
+}
+
+impl DeltaLakeManagerBuilder {
+    /// Creates an empty builder. Files are read with at most 8 concurrent downloads and
+    /// permission decisions are cached for `PermissionCache::DEFAULT_TTL`.
+    pub fn new() -> Self {
+        DeltaLakeManagerBuilder {
+            principal: None,
+            workspace_name: None,
+            base_url: None,
+            credentials: None,
+            api_client: None,
+            credential_service_url: None,
+            credential_service_token: None,
+            storage_options: HashMap::new(),
+            max_concurrent_reads: 8,
+            permission_cache: None,
+            audit_sink: None,
+        }
+    }
+
+    /// Creates a builder from resolved connection settings. The storage credential service is
+    /// only configured when `storage_url` or `storage_port` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Config` if `user_name`, the host, the credentials or part of the
+    /// storage credential service settings are missing.
+    pub fn from_config(config: &Config) -> Result<Self, Error> {
+        let mut builder: DeltaLakeManagerBuilder = DeltaLakeManagerBuilder::new()
+            .principal(&config.user_name()?)
+            .api_client(APIClient::from_config(config)?);
+        if config.storage_url.is_some() || config.storage_port.is_some() {
+            builder =
+                builder.credential_service(&config.storage_endpoint()?, &config.storage_token()?);
+        }
+        Ok(builder)
+    }
+
+    /// Sets the principal whose permissions are checked i.e. 'first.last@example.com'.
+    pub fn principal(mut self, principal: &str) -> Self {
+        self.principal = Some(principal.to_string());
+        self
+    }
+
+    /// Sets the Databricks workspace name i.e. 'adb-12345678912345.11.azuredatabricks.net'.
+    pub fn workspace(mut self, workspace_name: &str) -> Self {
+        self.workspace_name = Some(workspace_name.to_string());
+        self
+    }
+
+    /// Overrides the base URL of the workspace API. Defaults to `https://{workspace_name}`.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.to_string());
+        self
+    }
+
+    /// Sets the credential provider used to authenticate against the workspace.
+    pub fn credentials(mut self, credentials: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Some(Arc::new(credentials));
+        self
+    }
+
+    /// Uses an already configured API client instead of `workspace`, `base_url` and `credentials`.
+    pub fn api_client(mut self, api_client: APIClient) -> Self {
+        self.api_client = Some(api_client);
+        self
+    }
+
+    /// Fetches storage credentials from the storage credential service.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The service URL including the port i.e. 'http://localhost:8000'.
+    /// * `token` - The bearer token of the service, also used to decrypt the credentials it returns.
+    pub fn credential_service(mut self, url: &str, token: &str) -> Self {
+        self.credential_service_url = Some(url.trim_end_matches('/').to_string());
+        self.credential_service_token = Some(token.to_string());
+        self
+    }
+
+    /// Configures the storage credential service from the `STORAGE_URL`, `STORAGE_PORT` and
+    /// `STORAGE_TOKEN` environment variables.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Config` if any of the variables is not set.
+    pub fn credential_service_from_env(self) -> Result<Self, Error> {
+        let storage_url: String = required_env("STORAGE_URL")?;
+        let storage_port: String = required_env("STORAGE_PORT")?;
+        let storage_token: String = required_env("STORAGE_TOKEN")?;
+        Ok(self.credential_service(&format!("{}:{}", storage_url, storage_port), &storage_token))
+    }
+
+    /// Sets a single object store option i.e. `azure_storage_account_name`.
+    /// Refer to: https://delta-io.github.io/delta-rs/usage/loading-table/
+    pub fn storage_option(mut self, key: &str, value: &str) -> Self {
+        self.storage_options
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Adds object store options, overriding options returned by the storage credential service.
+    pub fn storage_options(mut self, storage_options: HashMap<String, String>) -> Self {
+        self.storage_options.extend(storage_options);
+        self
+    }
+
+    /// Sets the maximum number of files downloaded concurrently by parallel reads.
+    pub fn max_concurrent_reads(mut self, max_concurrent_reads: usize) -> Self {
+        self.max_concurrent_reads = max_concurrent_reads.max(1);
+        self
+    }
+
+    /// Sets how long permission decisions are cached. A TTL of zero checks permissions on every call.
+    pub fn permission_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.permission_cache = Some(Arc::new(PermissionCache::new(ttl)));
+        self
+    }
+
+    /// Shares a permission cache with other readers, so decisions are evaluated once per principal.
+    pub fn permission_cache(mut self, permission_cache: Arc<PermissionCache>) -> Self {
+        self.permission_cache = Some(permission_cache);
+        self
+    }
+
+    /// Records every read and write decision, with the table version and bytes transferred,
+    /// i.e. to a `JsonLinesAuditSink`. Nothing is audited unless a sink is set.
+    pub fn audit_sink(mut self, audit_sink: impl AuditSink + 'static) -> Self {
+        self.audit_sink = Some(Arc::new(audit_sink));
+        self
+    }
+
+    /// Fetches the storage credentials, authenticates the principal and builds the reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Config` if the principal, the workspace or any storage configuration
+    /// is missing, `Error::Credentials` if the storage credentials cannot be decrypted,
+    /// `Error::AuthenticationFailed` if the principal cannot be authenticated and
+    /// `Error::IdentityMismatch` if the credentials belong to another principal.
+    pub async fn build(self) -> Result<DeltaLakeManager, Error> {
+        let principal: String = self
+            .principal
+            .ok_or_else(|| Error::Config("principal is not set".to_string()))?;
+
+        let api_client: APIClient = match self.api_client {
+            Some(api_client) => api_client,
+            None => {
+                let workspace_name: String = self
+                    .workspace_name
+                    .ok_or_else(|| Error::Config("workspace is not set".to_string()))?;
+                let credentials: Arc<dyn CredentialProvider> = self
+                    .credentials
+                    .ok_or_else(|| Error::Config("credentials are not set".to_string()))?;
+                let mut builder: APIClientBuilder =
+                    APIClientBuilder::new(workspace_name, String::new()).credentials(credentials);
+                if let Some(base_url) = &self.base_url {
+                    builder = builder.base_url(base_url);
+                }
+                builder.build()?
+            }
+        };
+
+        let mut storage_options: HashMap<String, String> = HashMap::new();
+        match (&self.credential_service_url, &self.credential_service_token) {
+            (Some(url), Some(token)) => {
+                let storage_credentials: AzureDataLakeGen2Options =
+                    fetch_storage_credentials(&api_client, url, token).await?;
+                storage_options.extend(storage_credentials.to_hash_map());
+            }
+            _ if self.storage_options.is_empty() => {
+                return Err(Error::Config(
+                    "no storage credentials: set a credential service or storage options"
+                        .to_string(),
+                ));
+            }
+            _ => {}
+        }
+        storage_options.extend(self.storage_options);
+
+        let user: User = permissions::authenticate_user(api_client.clone(), &principal).await?;
+
+        let metastore_client: Client = Client::from_api_client(api_client.clone());
+
+        let reader: DeltaLakeManager = DeltaLakeManager {
+            storage_options,
+            api_client,
+            metastore_client,
+            user,
+            max_concurrent_reads: self.max_concurrent_reads,
+            permission_cache: self.permission_cache.unwrap_or_default(),
+            audit_sink: self.audit_sink,
+        };
+
+        // Call the register_handlers function
+        register_handlers(None);
+
+        Ok(reader)
+    }
+}
+
+/// Fetches and decrypts the storage credentials from the storage credential service.
+async fn fetch_storage_credentials(
+    api_client: &APIClient,
+    url: &str,
+    token: &str,
+) -> Result<AzureDataLakeGen2Options, Error> {
+    let storage_response: Response = api_client
+        .fetch(&format!("{}/api/storage_credentials", url), Some(token))
+        .await?;
+    let storage_json: AzureDataLakeGen2Options = storage_response.json().await?;
+
+    Ok(AzureDataLakeGen2Options::new(
+        decrypt_strings(&storage_json.azure_storage_account_name, token)?,
+        decrypt_strings(&storage_json.azure_client_id, token)?,
+        decrypt_strings(&storage_json.azure_client_secret, token)?,
+        decrypt_strings(&storage_json.azure_tenant_id, token)?,
+    ))
+}
+
+/// Reads a required environment variable.
+fn required_env(name: &str) -> Result<String, Error> {
+    env::var(name).map_err(|_| Error::Config(format!("{} not set", name)))
+}
+
+fn decrypt_strings(string_value: &str, key: &str) -> Result<String, Error> {
     let mc = new_magic_crypt!(key, 256);
-    let decrypted_string = mc.decrypt_base64_to_string(string_value).unwrap();
-    decrypted_string
+    mc.decrypt_base64_to_string(string_value)
+        .map_err(|e| Error::Credentials(e.to_string()))
 }
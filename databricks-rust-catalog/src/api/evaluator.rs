@@ -0,0 +1,359 @@
+use super::permissions::{EffectivePrivileges, Identity, Privilege, SecurableType};
+use serde::Serialize;
+
+/// Ownership and effective privileges of one object in the Unity Catalog hierarchy.
+#[derive(Debug, Clone)]
+pub struct SecurableFacts {
+    /// Type of the object.
+    pub securable_type: SecurableType,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// The user or group owning the object, if known.
+    pub owner: Option<String>,
+    /// Effective privileges on the object, including privileges inherited from its parents.
+    pub privileges: EffectivePrivileges,
+}
+
+/// Operation a principal wants to perform on an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Operation {
+    /// Read data, requires `SELECT`.
+    Read,
+    /// Add, update or delete data, requires `MODIFY`.
+    Write,
+    /// See the object's metadata, requires `BROWSE` or access to the object.
+    Browse,
+    /// Exercise a specific privilege on the object, i.e. `READ_VOLUME` on a volume.
+    Privilege(Privilege),
+}
+
+/// A single privilege the principal needs on an object and how it is held, if at all.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequirementCheck {
+    /// The required privilege i.e. 'USE_CATALOG'.
+    pub privilege: String,
+    /// Fully qualified name of the object the privilege is required on.
+    pub securable: String,
+    /// How the privilege is held i.e. 'SELECT granted to data-engineers on main'. `None` when missing.
+    pub satisfied_by: Option<String>,
+}
+
+impl RequirementCheck {
+    /// Returns true if the principal holds the privilege.
+    pub fn is_satisfied(&self) -> bool {
+        self.satisfied_by.is_some()
+    }
+}
+
+/// Outcome of evaluating an operation, with every requirement that was checked.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Decision {
+    /// The operation that was evaluated.
+    pub operation: Operation,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// True if the operation is allowed.
+    pub allowed: bool,
+    /// The requirements in the order they were checked.
+    pub checks: Vec<RequirementCheck>,
+}
+
+/// Trace of a decision explaining why a privilege is allowed or denied, serializable to JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccessExplanation {
+    /// The principal the decision was made for.
+    pub principal: String,
+    /// Every group of the principal, whose grants and ownership count as the principal's.
+    pub groups: Vec<String>,
+    /// The privilege that was checked.
+    pub privilege: Privilege,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// Ownership and grants of the principal on the object's parents and the object itself,
+    /// from the catalog down.
+    pub levels: Vec<LevelTrace>,
+    /// The requirements in the order they were checked.
+    pub checks: Vec<RequirementCheck>,
+    /// The requirements the principal does not meet, empty when allowed.
+    pub missing: Vec<RequirementCheck>,
+    /// True if the privilege is allowed.
+    pub allowed: bool,
+    /// One line summary i.e. 'denied: missing USE_SCHEMA on main.sales'.
+    pub verdict: String,
+}
+
+/// What the principal holds on one level of the hierarchy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LevelTrace {
+    /// Type of the object.
+    pub securable_type: SecurableType,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// The user or group owning the object, if known.
+    pub owner: Option<String>,
+    /// True if the owner is the principal or one of its groups.
+    pub owned: bool,
+    /// Privileges held on the object by the principal or its groups, directly or inherited.
+    pub grants: Vec<GrantTrace>,
+}
+
+/// A privilege held on an object and who holds it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GrantTrace {
+    /// The privilege i.e. 'SELECT'.
+    pub privilege: String,
+    /// The principal itself or the group holding the privilege.
+    pub held_by: String,
+    /// The parent object the privilege is inherited from, `None` when granted on the object.
+    pub inherited_from: Option<String>,
+}
+
+/// Evaluates access following the Unity Catalog privilege model.
+///
+/// * Accessing an object requires `USE_CATALOG` on its catalog and `USE_SCHEMA` on its schema,
+///   in addition to the privilege for the operation on the object itself (`SELECT` to read,
+///   `MODIFY` to write). Owners of the object are not exempt from the `USE_*` requirements.
+///   Privileges on a catalog or schema, such as `CREATE_TABLE`, require its own `USE_*` as well.
+///   Objects outside of catalogs, such as external locations, have no `USE_*` requirements.
+/// * Privileges granted on a catalog or schema are inherited by the objects they contain.
+///   `ALL_PRIVILEGES` satisfies every privilege on the object it is granted on and its children.
+/// * Owners hold every privilege on the object they own. Ownership is not inherited, so owning
+///   a catalog satisfies `USE_CATALOG` but does not allow reading its tables.
+/// * `BROWSE` on an object, usually inherited from its catalog, allows seeing its metadata
+///   without `USE_CATALOG` or `USE_SCHEMA`. Holding any privilege on the object, with the
+///   `USE_*` privileges on its parents, allows it as well.
+/// * Grants and ownership held by any group of the principal count as held by the principal.
+///
+/// Refer to: https://docs.databricks.com/en/data-governance/unity-catalog/manage-privileges/privileges.html
+pub struct PrivilegeEvaluator<'a> {
+    identity: &'a Identity,
+    levels: &'a [SecurableFacts],
+}
+
+impl<'a> PrivilegeEvaluator<'a> {
+    /// Creates an evaluator for a principal.
+    ///
+    /// # Arguments
+    ///
+    /// * `identity` - The principal and its groups.
+    /// * `levels` - The object and its parents ordered from the catalog down to the object,
+    ///   i.e. `main`, `main.sales`, `main.sales.orders`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let decision: Decision = PrivilegeEvaluator::new(&identity, &levels).evaluate(Operation::Read);
+    /// ```
+    pub fn new(identity: &'a Identity, levels: &'a [SecurableFacts]) -> Self {
+        PrivilegeEvaluator { identity, levels }
+    }
+
+    /// Evaluates an operation on the last level.
+    pub fn evaluate(&self, operation: Operation) -> Decision {
+        let full_name: String = self
+            .levels
+            .last()
+            .map(|object| object.full_name.clone())
+            .unwrap_or_default();
+
+        let (allowed, checks) = match operation {
+            Operation::Read => self.check_path(Privilege::Select),
+            Operation::Write => self.check_path(Privilege::Modify),
+            Operation::Browse | Operation::Privilege(Privilege::Browse) => self.check_browse(),
+            Operation::Privilege(privilege) => self.check_path(privilege),
+        };
+
+        Decision {
+            operation,
+            full_name,
+            allowed,
+            checks,
+        }
+    }
+
+    /// Evaluates a privilege on the last level and explains the decision.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let explanation: AccessExplanation = PrivilegeEvaluator::new(&identity, &levels).explain(Privilege::Select);
+    /// println!("{}", serde_json::to_string_pretty(&explanation)?);
+    /// ```
+    pub fn explain(&self, privilege: Privilege) -> AccessExplanation {
+        let decision: Decision = self.evaluate(Operation::Privilege(privilege));
+        let levels: Vec<LevelTrace> = self.levels.iter().map(|level| self.trace(level)).collect();
+        let missing: Vec<RequirementCheck> = decision
+            .checks
+            .iter()
+            .filter(|check| !check.is_satisfied())
+            .cloned()
+            .collect();
+
+        let verdict: String = if decision.allowed {
+            format!("allowed: {} on {}", privilege, decision.full_name)
+        } else {
+            let missing: Vec<String> = missing
+                .iter()
+                .map(|check| format!("{} on {}", check.privilege, check.securable))
+                .collect();
+            format!("denied: missing {}", missing.join(", "))
+        };
+
+        AccessExplanation {
+            principal: self.identity.user_name.clone(),
+            groups: self.identity.groups.clone(),
+            privilege,
+            full_name: decision.full_name,
+            levels,
+            checks: decision.checks,
+            missing,
+            allowed: decision.allowed,
+            verdict,
+        }
+    }
+
+    /// Lists ownership and every privilege held by the principal or its groups on a level.
+    fn trace(&self, level: &SecurableFacts) -> LevelTrace {
+        let grants: Vec<GrantTrace> = self
+            .identity
+            .names()
+            .into_iter()
+            .flat_map(|name| {
+                level
+                    .privileges
+                    .privileges_for(name)
+                    .map(move |grant| GrantTrace {
+                        privilege: grant.privilege.clone(),
+                        held_by: name.to_string(),
+                        inherited_from: grant.inherited_from_name.clone(),
+                    })
+            })
+            .collect();
+
+        LevelTrace {
+            securable_type: level.securable_type.clone(),
+            full_name: level.full_name.clone(),
+            owner: level.owner.clone(),
+            owned: level
+                .owner
+                .as_deref()
+                .is_some_and(|owner| self.identity.is(owner)),
+            grants,
+        }
+    }
+
+    /// Returns how the principal holds a privilege on an object, or `None` if it does not.
+    pub fn holds(&self, level: &SecurableFacts, privilege: Privilege) -> Option<String> {
+        if let Some(owner) = level
+            .owner
+            .as_deref()
+            .filter(|owner| self.identity.is(owner))
+        {
+            return Some(format!("owner {} of {}", owner, level.full_name));
+        }
+        for name in self.identity.names() {
+            let grant = level
+                .privileges
+                .privileges_for(name)
+                .find(|p| privilege.is_granted_by(&p.privilege));
+            if let Some(grant) = grant {
+                return Some(format!(
+                    "{} granted to {} on {}",
+                    grant.privilege,
+                    name,
+                    grant
+                        .inherited_from_name
+                        .as_deref()
+                        .unwrap_or(&level.full_name)
+                ));
+            }
+        }
+        None
+    }
+
+    /// Returns how the principal holds any privilege on an object, or `None` if it holds none.
+    fn holds_any(&self, level: &SecurableFacts) -> Option<String> {
+        if let Some(owner) = level
+            .owner
+            .as_deref()
+            .filter(|owner| self.identity.is(owner))
+        {
+            return Some(format!("owner {} of {}", owner, level.full_name));
+        }
+        self.identity.names().into_iter().find_map(|name| {
+            level
+                .privileges
+                .privileges_for(name)
+                .next()
+                .map(|grant| format!("{} granted to {}", grant.privilege, name))
+        })
+    }
+
+    /// Checks `USE_CATALOG` and `USE_SCHEMA` down to the object and `privilege` on the object.
+    fn check_path(&self, privilege: Privilege) -> (bool, Vec<RequirementCheck>) {
+        let Some(object) = self.levels.last() else {
+            return (false, Vec::new());
+        };
+
+        // catalogs and schemas need their own USE_* too, which may be the privilege being checked
+        let mut checks: Vec<RequirementCheck> = self.check_use_privileges(self.levels);
+        let covered: bool = checks
+            .iter()
+            .any(|c| c.securable == object.full_name && c.privilege == privilege.as_str());
+        if !covered {
+            checks.push(self.check(object, privilege));
+        }
+        (checks.iter().all(RequirementCheck::is_satisfied), checks)
+    }
+
+    fn check_browse(&self) -> (bool, Vec<RequirementCheck>) {
+        let Some((object, parents)) = self.levels.split_last() else {
+            return (false, Vec::new());
+        };
+
+        // BROWSE on the object, or on the catalog for catalog owners
+        let mut browse: RequirementCheck = self.check(object, Privilege::Browse);
+        if !browse.is_satisfied() {
+            if let Some(catalog) = parents.first() {
+                browse.satisfied_by = self.holds(catalog, Privilege::Browse);
+            }
+        }
+        if browse.is_satisfied() {
+            return (true, vec![browse]);
+        }
+
+        // otherwise any access to the object reveals its metadata
+        let mut checks: Vec<RequirementCheck> = vec![browse];
+        let path: Vec<RequirementCheck> = self.check_use_privileges(parents);
+        let any_privilege: RequirementCheck = RequirementCheck {
+            privilege: "ANY_PRIVILEGE".to_string(),
+            securable: object.full_name.clone(),
+            satisfied_by: self.holds_any(object),
+        };
+        let allowed: bool =
+            path.iter().all(RequirementCheck::is_satisfied) && any_privilege.is_satisfied();
+        checks.extend(path);
+        checks.push(any_privilege);
+        (allowed, checks)
+    }
+
+    /// Checks `USE_CATALOG` on catalogs and `USE_SCHEMA` on schemas among the levels.
+    fn check_use_privileges(&self, levels: &[SecurableFacts]) -> Vec<RequirementCheck> {
+        levels
+            .iter()
+            .filter_map(|level| match level.securable_type {
+                SecurableType::Catalog => Some(self.check(level, Privilege::UseCatalog)),
+                SecurableType::Schema => Some(self.check(level, Privilege::UseSchema)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn check(&self, level: &SecurableFacts, privilege: Privilege) -> RequirementCheck {
+        RequirementCheck {
+            privilege: privilege.to_string(),
+            securable: level.full_name.clone(),
+            satisfied_by: self.holds(level, privilege),
+        }
+    }
+}
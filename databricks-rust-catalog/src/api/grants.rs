@@ -0,0 +1,376 @@
+use super::api_client::APIClient;
+use super::permissions::{
+    self, PermissionsChange, Privilege, PrivilegeAssignmentsResponse, SecurableType,
+};
+use crate::error::Error;
+use indexmap::IndexMap;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+/// Desired direct grants, read from a grants file.
+///
+/// The file is TOML. Each table names an object as `[<securable_type>.<full_name>]` and maps
+/// principals to the privileges they should hold on it. Principals containing `@` or `.` and
+/// full names containing `.` must be quoted.
+///
+/// ```toml
+/// [catalog.main]
+/// data-engineers = ["USE_CATALOG", "BROWSE"]
+///
+/// [schema."main.sales"]
+/// data-engineers = ["USE_SCHEMA", "SELECT"]
+///
+/// [table."main.sales.orders"]
+/// "analyst@example.com" = ["SELECT"]
+/// ```
+///
+/// Every object listed in the file is managed exhaustively: privileges granted on it that are
+/// not in the file are revoked, and an empty table revokes every grant on the object. Objects
+/// that are not listed are left untouched.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(from = "GrantsTables")]
+pub struct GrantsFile {
+    /// The managed objects in the order they appear in the file.
+    pub objects: Vec<DesiredGrants>,
+}
+
+/// The privileges each principal should hold directly on one object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesiredGrants {
+    /// Type of the object.
+    pub securable_type: SecurableType,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// Privileges per principal.
+    pub principals: BTreeMap<String, BTreeSet<Privilege>>,
+}
+
+impl GrantsFile {
+    /// Reads a grants file from disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file cannot be read and `Error::InvalidGrants` if it is not a
+    /// valid grants file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+        let contents: String = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        GrantsFile::from_toml(&contents)
+    }
+
+    /// Parses the contents of a grants file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidGrants` with the line and column of the error if the contents are
+    /// not valid, i.e. an unknown securable type or privilege, or an object or principal listed
+    /// twice.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let grants: GrantsFile = GrantsFile::from_toml("[catalog.main]\ndata-engineers = [\"USE_CATALOG\"]")?;
+    /// ```
+    pub fn from_toml(contents: &str) -> Result<Self, Error> {
+        toml::from_str(contents).map_err(|e: toml::de::Error| {
+            // line and column of the start of the span, both starting at 1
+            let location: Option<(usize, usize)> = e.span().map(|span| {
+                let before: &str = &contents[..span.start];
+                let line_start: usize = before.rfind('\n').map_or(0, |i| i + 1);
+                (
+                    before.matches('\n').count() + 1,
+                    before[line_start..].chars().count() + 1,
+                )
+            });
+            Error::InvalidGrants {
+                line: location.map(|(line, _)| line),
+                column: location.map(|(_, column)| column),
+                message: e.message().to_string(),
+            }
+        })
+    }
+}
+
+/// Privileges per principal per object per securable type, in the order of the file.
+type GrantsTables =
+    IndexMap<SecurableTypeName, IndexMap<String, BTreeMap<String, Vec<PrivilegeName>>>>;
+
+impl From<GrantsTables> for GrantsFile {
+    fn from(tables: GrantsTables) -> Self {
+        let objects: Vec<DesiredGrants> = tables
+            .into_iter()
+            .flat_map(|(SecurableTypeName(securable_type), objects)| {
+                objects
+                    .into_iter()
+                    .map(move |(full_name, principals)| DesiredGrants {
+                        securable_type: securable_type.clone(),
+                        full_name,
+                        principals: principals
+                            .into_iter()
+                            .map(|(principal, privileges)| {
+                                (principal, privileges.into_iter().map(|p| p.0).collect())
+                            })
+                            .collect(),
+                    })
+            })
+            .collect();
+        GrantsFile { objects }
+    }
+}
+
+// a securable type as written in table headers i.e. 'catalog'
+#[derive(PartialEq, Eq, Hash)]
+struct SecurableTypeName(SecurableType);
+
+impl<'de> Deserialize<'de> for SecurableTypeName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name: String = String::deserialize(deserializer)?;
+        name.parse()
+            .map(SecurableTypeName)
+            .map_err(|_| de::Error::custom(format!("unknown securable type {}", name)))
+    }
+}
+
+// a privilege parsed like `Privilege::from_str`, so the error points at the unknown name
+struct PrivilegeName(Privilege);
+
+impl<'de> Deserialize<'de> for PrivilegeName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name: String = String::deserialize(deserializer)?;
+        name.parse()
+            .map(PrivilegeName)
+            .map_err(|_| de::Error::custom(format!("unknown privilege {}", name)))
+    }
+}
+
+/// Changes needed to make the direct grants on one object match the grants file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectPlan {
+    /// Type of the object.
+    pub securable_type: SecurableType,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// Privileges to grant and revoke per principal, sorted by principal.
+    pub changes: Vec<PermissionsChange>,
+}
+
+/// Changes needed to make the workspace match a grants file, for objects that drifted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    /// Objects with at least one change, in the order they appear in the file.
+    pub objects: Vec<ObjectPlan>,
+}
+
+impl Plan {
+    /// Returns true if the grants already match the file.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Returns the number of privileges to grant and to revoke.
+    pub fn counts(&self) -> (usize, usize) {
+        self.objects
+            .iter()
+            .flat_map(|object| object.changes.iter())
+            .fold((0, 0), |(add, remove), change| {
+                (add + change.add.len(), remove + change.remove.len())
+            })
+    }
+}
+
+impl fmt::Display for Plan {
+    /// Formats the plan with one line per principal and object, i.e.
+    ///
+    /// ```text
+    /// table main.sales.orders
+    ///   + GRANT SELECT TO data-engineers
+    ///   - REVOKE MODIFY FROM analyst@example.com
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes. Grants match the file.");
+        }
+        for object in &self.objects {
+            writeln!(f, "{} {}", object.securable_type, object.full_name)?;
+            for change in &object.changes {
+                if !change.add.is_empty() {
+                    writeln!(f, "  + GRANT {} TO {}", join(&change.add), change.principal)?;
+                }
+                if !change.remove.is_empty() {
+                    writeln!(
+                        f,
+                        "  - REVOKE {} FROM {}",
+                        join(&change.remove),
+                        change.principal
+                    )?;
+                }
+            }
+        }
+        let (add, remove) = self.counts();
+        writeln!(
+            f,
+            "Plan: {} to grant, {} to revoke on {} objects.",
+            add,
+            remove,
+            self.objects.len()
+        )
+    }
+}
+
+/// Computes the changes needed to make the direct grants on one object match the desired grants.
+///
+/// Privileges the API reports that are not known to `Privilege` are left untouched with a warning,
+/// since they cannot be expressed in the grants file. Principals that differ only in case are
+/// the same principal, and changes keep the spelling the workspace reports.
+pub fn diff(desired: &DesiredGrants, current: &PrivilegeAssignmentsResponse) -> ObjectPlan {
+    let mut held: Principals = BTreeMap::new();
+    for assignment in current.privilege_assignments.iter().flatten() {
+        let Some(principal) = &assignment.principal else {
+            continue;
+        };
+        let privileges: &mut BTreeSet<Privilege> = &mut held
+            .entry(principal.to_ascii_lowercase())
+            .or_insert_with(|| (principal.clone(), BTreeSet::new()))
+            .1;
+        for name in assignment.privileges.iter().flatten() {
+            match name.parse::<Privilege>() {
+                Ok(privilege) => {
+                    privileges.insert(privilege);
+                }
+                Err(_) => log::warn!(
+                    "Ignoring unknown privilege {} granted to {} on {}",
+                    name,
+                    principal,
+                    desired.full_name
+                ),
+            }
+        }
+    }
+    let mut wanted: Principals = BTreeMap::new();
+    for (principal, privileges) in &desired.principals {
+        wanted
+            .entry(principal.to_ascii_lowercase())
+            .or_insert_with(|| (principal.clone(), BTreeSet::new()))
+            .1
+            .extend(privileges);
+    }
+
+    let principals: BTreeSet<&String> = held.keys().chain(wanted.keys()).collect();
+    let empty: BTreeSet<Privilege> = BTreeSet::new();
+    let changes: Vec<PermissionsChange> = principals
+        .into_iter()
+        .filter_map(|key| {
+            let want: &BTreeSet<Privilege> = wanted.get(key).map_or(&empty, |(_, p)| p);
+            let have: &BTreeSet<Privilege> = held.get(key).map_or(&empty, |(_, p)| p);
+            let add: Vec<Privilege> = want.difference(have).copied().collect();
+            let remove: Vec<Privilege> = have.difference(want).copied().collect();
+            if add.is_empty() && remove.is_empty() {
+                return None;
+            }
+            // grants go to the principal as spelled by the workspace if it already holds any
+            let (principal, _) = held.get(key).or_else(|| wanted.get(key))?;
+            Some(
+                PermissionsChange::new(principal)
+                    .grant(&add)
+                    .revoke(&remove),
+            )
+        })
+        .collect();
+
+    ObjectPlan {
+        securable_type: desired.securable_type.clone(),
+        full_name: desired.full_name.clone(),
+        changes,
+    }
+}
+
+/// Privileges per principal keyed by the lowercased principal, along with its spelling, since
+/// principals are matched case-insensitively like `Identity::is`.
+type Principals = BTreeMap<String, (String, BTreeSet<Privilege>)>;
+
+/// Compares the grants file with the direct grants in the workspace.
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `grants` - The desired grants.
+///
+/// # Returns
+///
+/// * `Plan` - The changes needed for every object that drifted from the file.
+///
+/// # Errors
+///
+/// Returns an `Error` if the current grants of an object cannot be fetched.
+///
+/// # Examples
+///
+/// ```ignore
+/// let plan: Plan = grants::plan(api_client, &GrantsFile::load("grants.toml")?).await?;
+/// println!("{}", plan);
+/// ```
+pub async fn plan(api_client: APIClient, grants: &GrantsFile) -> Result<Plan, Error> {
+    let mut objects: Vec<ObjectPlan> = Vec::new();
+    for desired in &grants.objects {
+        let current: PrivilegeAssignmentsResponse = permissions::get_permissions(
+            api_client.clone(),
+            desired.securable_type.clone(),
+            &desired.full_name,
+        )
+        .await?;
+        let object: ObjectPlan = diff(desired, &current);
+        if !object.changes.is_empty() {
+            objects.push(object);
+        }
+    }
+    Ok(Plan { objects })
+}
+
+/// Applies a plan, updating the grants of one object per request.
+///
+/// Objects are updated in plan order and the first failure stops the apply, leaving the
+/// objects after it unchanged. Running `plan` again shows what is left.
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `plan` - The plan to apply, usually returned by `plan`.
+///
+/// # Returns
+///
+/// * `Vec<PrivilegeAssignmentsResponse>` - The direct grants of each updated object.
+///
+/// # Errors
+///
+/// Returns an `Error` if an update is rejected, i.e. when the caller cannot manage grants on the object.
+pub async fn apply(
+    api_client: APIClient,
+    plan: &Plan,
+) -> Result<Vec<PrivilegeAssignmentsResponse>, Error> {
+    let mut results: Vec<PrivilegeAssignmentsResponse> = Vec::new();
+    for object in &plan.objects {
+        let grants: PrivilegeAssignmentsResponse = permissions::update_permissions(
+            api_client.clone(),
+            object.securable_type.clone(),
+            &object.full_name,
+            &object.changes,
+        )
+        .await?;
+        results.push(grants);
+    }
+    Ok(results)
+}
+
+fn join(privileges: &[Privilege]) -> String {
+    privileges
+        .iter()
+        .map(Privilege::as_str)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
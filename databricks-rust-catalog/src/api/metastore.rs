@@ -1,6 +1,11 @@
 use super::api_client::APIClient;
-use reqwest::{Error, Response};
+use super::pagination::Page;
+use crate::error::Error;
+use futures::future;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use reqwest::{Response, Url};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Clone)]
 pub struct Client {
@@ -21,121 +26,172 @@ impl Client {
     ///  let metastore_client: Client = Client::new(workspace_name.clone(), db_token.clone());
     /// ```    
     pub fn new(workspace_name: String, db_token: String) -> Self {
-        let api_client: APIClient = APIClient {
-            db_token: db_token,
-            workspace_name: workspace_name,
-        };
+        let api_client: APIClient = APIClient::new(workspace_name, db_token);
 
-        let perms: Client = Client {
-            api_client: api_client,
-        };
+        let perms: Client = Client { api_client };
 
         perms
     }
 
+    /// Creates a metastore client from an existing API client, keeping its retry policy.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let metastore_client: Client = Client::from_api_client(api_client.clone());
+    /// ```
+    pub fn from_api_client(api_client: APIClient) -> Self {
+        Client { api_client }
+    }
+
+    /// Streams the catalogs in a Databricks' Unity Catalog Metastore, following every page
+    /// - https://docs.databricks.com/api/workspace/catalogs/list
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Listing filters, see `ListOptions`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    ///  let mut catalogs = client.stream_catalogs(&ListOptions::default());
+    ///  while let Some(catalog) = catalogs.try_next().await? { ... }
+    /// ```
+    pub fn stream_catalogs(
+        &self,
+        options: &ListOptions,
+    ) -> BoxStream<'static, Result<Catalog, Error>> {
+        let options: ListOptions = options.clone();
+
+        self.list::<CatalogResponse>("/api/2.1/unity-catalog/catalogs", &[], &options)
+            .try_filter(move |catalog| future::ready(options.matches(&catalog.name, None)))
+            .boxed()
+    }
+
     /// List all catalogs in a Databricks' Unity Catalog Metastore
     /// - https://docs.databricks.com/api/workspace/catalogs/list
     ///
+    /// # Arguments
+    ///
+    /// * `options` - Listing filters, see `ListOptions`.
+    ///
     /// # Examples
     ///
     /// ```ignore
-    ///  client.fetch_catalogs();
-    /// ```    
-    #[allow(dead_code)]
-    async fn fetch_catalogs(&self) -> Result<CatalogResponse, Error> {
-        let catalog_url: String = format!(
-            "https://{}/api/2.1/unity-catalog/catalogs",
-            &self.api_client.workspace_name
-        );
-
-        let response: Response = self.api_client.fetch(&catalog_url, None).await?;
-
-        let catalogs: CatalogResponse = match response.json().await {
-            Ok(catalogs) => catalogs, // If deserialization succeeds, continue with the deserialized data
-            Err(e) => {
-                // If deserialization fails, log the error and return an error
-                log::error!("Error deserializing JSON response: {}", e);
-                return Err(e.into());
-            }
-        };
+    ///  let catalogs: Vec<Catalog> = client.list_catalogs(&ListOptions::default().include_browse(true)).await?;
+    /// ```
+    pub async fn list_catalogs(&self, options: &ListOptions) -> Result<Vec<Catalog>, Error> {
+        self.stream_catalogs(options)
+            .try_collect()
+            .await
+            .inspect_err(|e| log::error!("Error listing catalogs: {}", e))
+    }
 
-        Ok(catalogs)
+    /// Streams the schemas of a given catalog, following every page
+    /// - https://docs.databricks.com/api/workspace/schemas/list
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog_name` - The catalog containing the schemas.
+    /// * `options` - Listing filters, see `ListOptions`.
+    pub fn stream_schemas(
+        &self,
+        catalog_name: &str,
+        options: &ListOptions,
+    ) -> BoxStream<'static, Result<Schema, Error>> {
+        let options: ListOptions = options.clone();
+
+        self.list::<SchemaResponse>(
+            "/api/2.1/unity-catalog/schemas",
+            &[("catalog_name", catalog_name)],
+            &options,
+        )
+        .try_filter(move |schema| future::ready(options.matches(&schema.name, None)))
+        .boxed()
     }
 
-    /// List schemas fpr a given catalog in a Databricks' Unity Catalog Metastore
+    /// List schemas for a given catalog in a Databricks' Unity Catalog Metastore
     /// - https://docs.databricks.com/api/workspace/schemas/list
     ///
+    /// # Arguments
+    ///
+    /// * `catalog_name` - The catalog containing the schemas.
+    /// * `options` - Listing filters, see `ListOptions`.
+    ///
     /// # Examples
     ///
     /// ```ignore
-    ///  client.fetch_schemas(catalog_name);
-    /// ```  
-    #[allow(dead_code)]
-    async fn fetch_schemas(
+    ///  let schemas: Vec<Schema> = client.list_schemas("main", &ListOptions::default().name_pattern("sales_*")).await?;
+    /// ```
+    pub async fn list_schemas(
         &self,
-        catalog_name: String,
-        max_results: Option<usize>,
-    ) -> Result<SchemaResponse, Error> {
-        let mut schema_url = format!(
-            "https://{}/api/2.1/unity-catalog/schemas?catalog_name={}",
-            &self.api_client.workspace_name, catalog_name
-        );
-
-        if let Some(max) = max_results {
-            schema_url.push_str(&format!("&max_results={}", max));
-        }
-
-        // Fetch schemas for the current catalog
-        let response: Response = self.api_client.fetch(&schema_url, None).await?;
-        let schemas: SchemaResponse = match response.json().await {
-            Ok(schemas) => schemas, // If deserialization succeeds, continue with the deserialized data
-            Err(e) => {
-                // If deserialization fails, log the error and return an error
-                log::error!("Error deserializing JSON response: {}", e);
-                return Err(e.into());
-            }
-        };
+        catalog_name: &str,
+        options: &ListOptions,
+    ) -> Result<Vec<Schema>, Error> {
+        self.stream_schemas(catalog_name, options)
+            .try_collect()
+            .await
+            .inspect_err(|e| log::error!("Error listing schemas in {}: {}", catalog_name, e))
+    }
 
-        Ok(schemas)
+    /// Streams the tables of a given schema, following every page
+    /// - https://docs.databricks.com/api/workspace/tables/list
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog_name` - The catalog containing the schema.
+    /// * `schema_name` - The schema containing the tables.
+    /// * `options` - Listing filters, see `ListOptions`.
+    pub fn stream_tables(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        options: &ListOptions,
+    ) -> BoxStream<'static, Result<Table, Error>> {
+        let options: ListOptions = options.clone();
+
+        self.list::<TableResponse>(
+            "/api/2.1/unity-catalog/tables",
+            &[("catalog_name", catalog_name), ("schema_name", schema_name)],
+            &options,
+        )
+        .try_filter(move |table| {
+            future::ready(options.matches(&table.name, Some(&table.table_type)))
+        })
+        .boxed()
     }
 
     /// List all tables for a given schema/catalog in a Databricks' Unity Catalog Metastore
     /// - https://docs.databricks.com/api/workspace/tables/list
     ///
+    /// # Arguments
+    ///
+    /// * `catalog_name` - The catalog containing the schema.
+    /// * `schema_name` - The schema containing the tables.
+    /// * `options` - Listing filters, see `ListOptions`.
+    ///
     /// # Examples
     ///
     /// ```ignore
-    ///  client.fetch_tables(catalog_name);
+    ///  let views: Vec<Table> = client.list_tables("main", "sales", &ListOptions::default().table_type("VIEW")).await?;
     /// ```
-    #[allow(dead_code)]
-    async fn fetch_tables(
+    pub async fn list_tables(
         &self,
-        catalog_name: String,
-        schema_name: String,
-        max_results: Option<usize>,
-    ) -> Result<TableResponse, Error> {
-        let mut table_url = format!(
-            "https://{}/api/2.1/unity-catalog/tables?catalog_name={}&schema_name={}",
-            &self.api_client.workspace_name, catalog_name, schema_name
-        );
-
-        if let Some(max) = max_results {
-            table_url.push_str(&format!("&max_results={}", max));
-        }
-
-        // Fetch tables for the current catalog/schema
-        let response: Response = self.api_client.fetch(&table_url, None).await?;
-        // let tables: TableResponse = response.json().await?;
-        let tables: TableResponse = match response.json().await {
-            Ok(tables) => tables, // If deserialization succeeds, continue with the deserialized data
-            Err(e) => {
-                // If deserialization fails, log the error and return an error
-                log::error!("Error deserializing JSON response: {}", e);
-                return Err(e.into());
-            }
-        };
-
-        Ok(tables)
+        catalog_name: &str,
+        schema_name: &str,
+        options: &ListOptions,
+    ) -> Result<Vec<Table>, Error> {
+        self.stream_tables(catalog_name, schema_name, options)
+            .try_collect()
+            .await
+            .inspect_err(|e| {
+                log::error!(
+                    "Error listing tables in {}.{}: {}",
+                    catalog_name,
+                    schema_name,
+                    e
+                )
+            })
     }
 
     /// Get an individual table object
@@ -147,12 +203,18 @@ impl Client {
     ///  client.get_table(!format("{}.{}.{}", catalog_name, schema_name, table_name));
     /// ```  
     pub async fn get_table(&self, full_table_name: &str) -> Result<Table, Error> {
-        let url: String = format!(
-            "https://{}/api/2.1/unity-catalog/tables/{}",
-            &self.api_client.workspace_name, full_table_name
-        );
-
-        let response: Response = self.api_client.fetch(&url, None).await?;
+        let url: String = self.api_client.url(&format!(
+            "/api/2.1/unity-catalog/tables/{}",
+            full_table_name
+        ));
+
+        let response: Response = match self.api_client.fetch(&url, None).await {
+            Ok(response) => response,
+            Err(Error::Api(e)) if e.is_not_found() => {
+                return Err(Error::TableNotFound(full_table_name.to_string()))
+            }
+            Err(e) => return Err(e),
+        };
         let table: Table = response.json().await?;
 
         Ok(table)
@@ -167,10 +229,10 @@ impl Client {
     ///  client.get_schema(!format("{}.{}", catalog_name, schema_name));
     /// ```  
     pub async fn get_schema(&self, full_schema_name: String) -> Result<Schema, Error> {
-        let url: String = format!(
-            "https://{}/api/2.1/unity-catalog/schemas/{}",
-            &self.api_client.workspace_name, full_schema_name
-        );
+        let url: String = self.api_client.url(&format!(
+            "/api/2.1/unity-catalog/schemas/{}",
+            full_schema_name
+        ));
 
         let response: Response = self.api_client.fetch(&url, None).await?;
         let schema: Schema = response.json().await?;
@@ -187,26 +249,147 @@ impl Client {
     ///  client.get_catalog(catalog_name);
     /// ```  
     pub async fn get_catalog(&self, name: String) -> Result<Catalog, Error> {
-        let url: String = format!(
-            "https://{}/api/2.1/unity-catalog/catalogs/{}",
-            &self.api_client.workspace_name, name
-        );
+        let url: String = self
+            .api_client
+            .url(&format!("/api/2.1/unity-catalog/catalogs/{}", name));
 
         let response: Response = self.api_client.fetch(&url, None).await?;
         let catalog: Catalog = response.json().await?;
 
         Ok(catalog)
     }
+
+    /// Streams the items of a list endpoint, URL encoding the query parameters and appending
+    /// the server side filters of the options.
+    fn list<P: Page>(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+        options: &ListOptions,
+    ) -> BoxStream<'static, Result<P::Item, Error>> {
+        let mut url: Url = match self.api_client.parse_url(path) {
+            Ok(url) => url,
+            Err(e) => return stream::once(future::ready(Err(e))).boxed(),
+        };
+        options.apply_query(&mut url, params);
+        self.api_client.paginate::<P>(url.as_str())
+    }
+}
+
+/// Filters applied when listing catalogs, schemas and tables.
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Include objects the principal can only browse (BROWSE privilege) in the listing.
+    pub include_browse: bool,
+    /// Page size requested from the API. All pages are returned regardless.
+    pub max_results: Option<usize>,
+    /// Case insensitive name pattern where `*` matches any run of characters and `?` a single character.
+    pub name_pattern: Option<String>,
+    /// Only return tables of this type i.e. `MANAGED`, `EXTERNAL` or `VIEW`. Ignored for catalogs and schemas.
+    pub table_type: Option<String>,
+}
+
+impl ListOptions {
+    /// Includes objects the principal can only browse.
+    pub fn include_browse(mut self, include_browse: bool) -> Self {
+        self.include_browse = include_browse;
+        self
+    }
+
+    /// Sets the page size requested from the API.
+    pub fn max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Only returns objects whose name matches the pattern i.e. `sales_*`.
+    pub fn name_pattern(mut self, name_pattern: &str) -> Self {
+        self.name_pattern = Some(name_pattern.to_string());
+        self
+    }
+
+    /// Only returns tables of the given type.
+    pub fn table_type(mut self, table_type: &str) -> Self {
+        self.table_type = Some(table_type.to_string());
+        self
+    }
+
+    /// Appends the query parameters and the server side filters to a list URL.
+    fn apply_query(&self, url: &mut Url, params: &[(&str, &str)]) {
+        let max_results: Option<String> = self.max_results.map(|max| max.to_string());
+        let mut pairs: Vec<(&str, &str)> = params.to_vec();
+        if self.include_browse {
+            pairs.push(("include_browse", "true"));
+        }
+        if let Some(max) = &max_results {
+            pairs.push(("max_results", max));
+        }
+        // an empty serializer would still leave a trailing `?`
+        if !pairs.is_empty() {
+            url.query_pairs_mut().extend_pairs(pairs);
+        }
+    }
+
+    /// Applies the client side filters to a listed object.
+    fn matches(&self, name: &str, table_type: Option<&str>) -> bool {
+        if let Some(pattern) = &self.name_pattern {
+            if !matches_pattern(&pattern.to_lowercase(), &name.to_lowercase()) {
+                return false;
+            }
+        }
+        match (&self.table_type, table_type) {
+            (Some(wanted), Some(actual)) => wanted.eq_ignore_ascii_case(actual),
+            _ => true,
+        }
+    }
+}
+
+/// Glob style matching supporting `*` and `?`.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` in the pattern and the name index it was matched at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 // wrapper struct to contain a vector of catalogs
 #[derive(Debug, Deserialize, Clone)]
 pub struct CatalogResponse {
+    #[serde(default)]
     pub catalogs: Vec<Catalog>,
+    pub next_page_token: Option<String>,
 }
 impl CatalogResponse {
     pub fn new(catalogs: Vec<Catalog>) -> Self {
-        CatalogResponse { catalogs: catalogs }
+        CatalogResponse {
+            catalogs,
+            next_page_token: None,
+        }
+    }
+}
+impl Page for CatalogResponse {
+    type Item = Catalog;
+
+    fn into_parts(self) -> (Vec<Catalog>, Option<String>) {
+        (self.catalogs, self.next_page_token)
     }
 }
 
@@ -233,11 +416,10 @@ pub struct Catalog {
     pub securable_kind: Option<String>,
     pub securable_type: Option<String>,
     pub browse_only: Option<bool>,
-    // excluded fields due to nesting
-    // properties
-    // effective_predictive_optimization_flag
-    // options
-    // provisioning_info
+    pub properties: Option<HashMap<String, String>>,
+    pub options: Option<HashMap<String, String>>,
+    pub provisioning_info: Option<ProvisioningInfo>,
+    pub effective_predictive_optimization_flag: Option<EffectivePredictiveOptimizationFlag>,
 }
 
 /// Represents a response containing schemas.
@@ -245,6 +427,8 @@ pub struct Catalog {
 pub struct SchemaResponse {
     /// Optional vector of schemas.
     pub schemas: Option<Vec<Schema>>,
+    /// Token of the next page, if there are more schemas to list.
+    pub next_page_token: Option<String>,
 }
 
 impl SchemaResponse {
@@ -257,9 +441,17 @@ impl SchemaResponse {
     pub fn new(schemas: Vec<Schema>) -> Self {
         SchemaResponse {
             schemas: Some(schemas),
+            next_page_token: None,
         }
     }
 }
+impl Page for SchemaResponse {
+    type Item = Schema;
+
+    fn into_parts(self) -> (Vec<Schema>, Option<String>) {
+        (self.schemas.unwrap_or_default(), self.next_page_token)
+    }
+}
 
 // represents a schema object in unity catalog
 #[derive(Debug, Deserialize, Clone)]
@@ -280,11 +472,14 @@ pub struct Schema {
     pub catalog_type: Option<String>,
     pub browse_only: Option<bool>,
     pub schema_id: String,
+    pub properties: Option<HashMap<String, String>>,
+    pub effective_predictive_optimization_flag: Option<EffectivePredictiveOptimizationFlag>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct TableResponse {
     pub tables: Option<Vec<Table>>,
+    pub next_page_token: Option<String>,
 }
 impl TableResponse {
     /// Constructs a new `TableResponse` with the provided vector of tables.
@@ -297,9 +492,17 @@ impl TableResponse {
     pub fn new(tables: Vec<Table>) -> Self {
         TableResponse {
             tables: Some(tables),
+            next_page_token: None,
         }
     }
 }
+impl Page for TableResponse {
+    type Item = Table;
+
+    fn into_parts(self) -> (Vec<Table>, Option<String>) {
+        (self.tables.unwrap_or_default(), self.next_page_token)
+    }
+}
 
 // represents a table object in unity catalog
 #[derive(Debug, Deserialize, Clone)]
@@ -328,12 +531,135 @@ pub struct Table {
     pub access_point: Option<String>,
     pub pipeline_id: Option<String>,
     pub browse_only: Option<bool>,
-    // excluded fields due to nesting
-    // columns
-    // dependencies
-    // properties
-    // table_constraints
-    // row_filter
-    // delta_runtime_properties_kvpairs
-    // effective_predictive_optimization_flag
+    pub columns: Option<Vec<ColumnInfo>>,
+    pub properties: Option<HashMap<String, String>>,
+    pub table_constraints: Option<Vec<TableConstraint>>,
+    pub row_filter: Option<RowFilter>,
+    pub dependencies: Option<DependencyList>,
+    pub delta_runtime_properties_kvpairs: Option<DeltaRuntimeProperties>,
+    pub effective_predictive_optimization_flag: Option<EffectivePredictiveOptimizationFlag>,
+}
+
+impl Table {
+    /// Returns the column with the given name, compared case insensitively.
+    pub fn column(&self, name: &str) -> Option<&ColumnInfo> {
+        self.columns
+            .as_ref()?
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the partition columns ordered by their partition index.
+    pub fn partition_columns(&self) -> Vec<&ColumnInfo> {
+        let mut partitions: Vec<&ColumnInfo> = self
+            .columns
+            .iter()
+            .flatten()
+            .filter(|c| c.partition_index.is_some())
+            .collect();
+        partitions.sort_by_key(|c| c.partition_index);
+        partitions
+    }
+}
+
+// represents a column of a table in unity catalog
+#[derive(Debug, Deserialize, Clone)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub type_text: Option<String>,
+    /// Full data type specification as JSON-serialized text i.e. `{"name":"id","type":"long","nullable":true,"metadata":{}}`.
+    pub type_json: Option<String>,
+    pub type_name: Option<String>,
+    pub type_precision: Option<i32>,
+    pub type_scale: Option<i32>,
+    pub type_interval_type: Option<String>,
+    pub position: Option<i32>,
+    pub comment: Option<String>,
+    pub nullable: Option<bool>,
+    pub partition_index: Option<i32>,
+    pub mask: Option<ColumnMask>,
+}
+
+// column mask function applied to a column
+#[derive(Debug, Deserialize, Clone)]
+pub struct ColumnMask {
+    pub function_name: String,
+    pub using_column_names: Option<Vec<String>>,
+}
+
+// row filter function applied to a table
+#[derive(Debug, Deserialize, Clone)]
+pub struct RowFilter {
+    pub function_name: String,
+    pub input_column_names: Option<Vec<String>>,
+}
+
+// a table constraint, exactly one of the fields is set
+#[derive(Debug, Deserialize, Clone)]
+pub struct TableConstraint {
+    pub primary_key_constraint: Option<PrimaryKeyConstraint>,
+    pub foreign_key_constraint: Option<ForeignKeyConstraint>,
+    pub named_table_constraint: Option<NamedTableConstraint>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PrimaryKeyConstraint {
+    pub name: String,
+    pub child_columns: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ForeignKeyConstraint {
+    pub name: String,
+    pub child_columns: Vec<String>,
+    pub parent_table: String,
+    pub parent_columns: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct NamedTableConstraint {
+    pub name: String,
+}
+
+// tables and functions a view or metric depends on
+#[derive(Debug, Deserialize, Clone)]
+pub struct DependencyList {
+    pub dependencies: Option<Vec<Dependency>>,
+}
+
+// a single dependency, exactly one of the fields is set
+#[derive(Debug, Deserialize, Clone)]
+pub struct Dependency {
+    pub table: Option<TableDependency>,
+    pub function: Option<FunctionDependency>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TableDependency {
+    pub table_full_name: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct FunctionDependency {
+    pub function_full_name: String,
+}
+
+// delta runtime properties of a table
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeltaRuntimeProperties {
+    pub delta_runtime_properties: HashMap<String, String>,
+}
+
+// predictive optimization flag and where it is inherited from
+#[derive(Debug, Deserialize, Clone)]
+pub struct EffectivePredictiveOptimizationFlag {
+    pub value: String,
+    pub inherited_from_type: Option<String>,
+    pub inherited_from_name: Option<String>,
+}
+
+// provisioning status of a catalog
+#[derive(Debug, Deserialize, Clone)]
+pub struct ProvisioningInfo {
+    pub state: Option<String>,
 }
@@ -0,0 +1,88 @@
+use super::api_client::APIClient;
+use crate::error::Error;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+
+/// A single page returned by a Unity Catalog list endpoint.
+///
+/// List endpoints return their items under an endpoint specific key (`catalogs`, `schemas`,
+/// `tables`, ...) together with an optional `next_page_token`. Implementing this trait for the
+/// response wrapper lets `APIClient::paginate` follow the tokens for any endpoint.
+pub trait Page: DeserializeOwned + Send + 'static {
+    /// The item type contained in the page.
+    type Item: Send + 'static;
+
+    /// Splits the page into its items and the token of the next page, if any.
+    fn into_parts(self) -> (Vec<Self::Item>, Option<String>);
+}
+
+impl APIClient {
+    /// Streams every item of a paginated list endpoint, following `next_page_token` until the
+    /// last page. Pages are requested lazily as the stream is polled.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The list API path including any query parameters i.e. '/api/2.1/unity-catalog/schemas?catalog_name=main'.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut tables = api_client.paginate::<TableResponse>("/api/2.1/unity-catalog/tables?catalog_name=main&schema_name=sales");
+    /// while let Some(table) = tables.try_next().await? {
+    ///     println!("{}", table.full_name);
+    /// }
+    /// ```
+    pub fn paginate<P: Page>(&self, path: &str) -> BoxStream<'static, Result<P::Item, Error>> {
+        let client: APIClient = self.clone();
+        let url: String = self.url(path);
+
+        // state is `Some(page_token)` while there are pages left to request
+        stream::try_unfold(Some(None::<String>), move |state| {
+            let client: APIClient = client.clone();
+            let url: String = url.clone();
+            async move {
+                let page_token: Option<String> = match state {
+                    Some(page_token) => page_token,
+                    None => return Ok::<_, Error>(None),
+                };
+
+                let page_url: String =
+                    with_page_token(client.parse_url(&url)?, page_token.as_deref());
+                log::debug!("Fetching page - {}", page_url);
+                let page: P = client.get(&page_url).await?;
+                let (items, next_page_token) = page.into_parts();
+
+                let next_state: Option<Option<String>> =
+                    next_page_token.filter(|token| !token.is_empty()).map(Some);
+                let items = stream::iter(items.into_iter().map(Ok::<P::Item, Error>));
+                Ok(Some((items, next_state)))
+            }
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    /// Collects every item of a paginated list endpoint into a vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The list API path including any query parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let catalogs: Vec<Catalog> = api_client.paginate_all::<CatalogResponse>("/api/2.1/unity-catalog/catalogs").await?;
+    /// ```
+    pub async fn paginate_all<P: Page>(&self, path: &str) -> Result<Vec<P::Item>, Error> {
+        self.paginate::<P>(path).try_collect().await
+    }
+}
+
+/// Adds the `page_token` query parameter to a URL.
+fn with_page_token(mut url: Url, page_token: Option<&str>) -> String {
+    if let Some(page_token) = page_token {
+        url.query_pairs_mut().append_pair("page_token", page_token);
+    }
+    url.to_string()
+}
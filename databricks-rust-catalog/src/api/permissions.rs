@@ -1,117 +1,405 @@
 use super::api_client::APIClient;
-use reqwest::{Error, Response};
-use serde::Deserialize;
+use super::evaluator::{
+    AccessExplanation, Decision, Operation, PrivilegeEvaluator, SecurableFacts,
+};
+use crate::error::Error;
+use reqwest::{Response, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 
-/// Returns User Struct containing the princpal used for authentication.
+/// Verifies that the token of the API client belongs to the claimed principal.
+///
+/// The identity is taken from the SCIM `/Me` endpoint, which only ever describes the owner of the
+/// token. The claimed principal must match its user name, case insensitively, or for a service
+/// principal its application id. The returned `User` is the identity to use for every
+/// permission check afterwards.
 ///
 /// # Arguments
 ///
-/// * `user_name` - The username associated to the token
-/// * `user_token` - The token used for authentication against Unity Catalog
+/// * `api_client` - API client object for making HTTP requests.
+/// * `principal` - The claimed principal i.e. 'first.last@example.com' or the application id of a service principal.
+///
+/// # Returns
+///
+/// * `User` - The user or service principal the token belongs to.
+///
+/// # Errors
+///
+/// Returns `Error::AuthenticationFailed` if the token is rejected or the principal is not active,
+/// `Error::IdentityMismatch` if the token belongs to another principal, or an `Error` if the
+/// request fails or the response cannot be parsed.
 ///
 /// # Examples
 ///
 /// ```ignore
-/// let active_user: api::permissions::User = permissions_client.authenticate_user("ryan.chynoweth@databricks.com", db_token, workspace_name).await.unwrap();
+/// let user: User = authenticate_user(api_client, "first.last@example.com").await?;
+/// let allowed: bool = can_read(api_client, "main.sales.orders", user.principal()).await?;
 /// ```
-pub async fn authenticate_user(api_client: APIClient, user_name: &str) -> Result<bool, Error> {
-    // need to add encryption and verification of user
+pub async fn authenticate_user(api_client: APIClient, principal: &str) -> Result<User, Error> {
+    let user: User = api_client
+        .get("/api/2.0/preview/scim/v2/Me")
+        .await
+        .map_err(|e| match e {
+            Error::Api(api_error) if api_error.status == 401 || api_error.status == 403 => {
+                Error::AuthenticationFailed(format!(
+                    "the token was rejected for {}: {}",
+                    principal, api_error.message
+                ))
+            }
+            e => e,
+        })?;
 
-    // user_token will likely be required in the future as there will be a service token and a user token.
-    let auth_url: String = format!(
-        "https://{}/api/2.0/preview/scim/v2/Me",
-        api_client.workspace_name
-    );
+    if !user.is(principal) {
+        log::error!(
+            "Failed to authenticate {}: the token belongs to {}",
+            principal,
+            user.principal()
+        );
+        return Err(Error::IdentityMismatch {
+            claimed: principal.to_string(),
+            actual: user.principal().to_string(),
+        });
+    }
+    if !user.active {
+        log::error!(
+            "Failed to authenticate {}: the principal is not active",
+            principal
+        );
+        return Err(Error::AuthenticationFailed(format!(
+            "{} is not active",
+            user.principal()
+        )));
+    }
 
-    let response: Response = api_client.fetch(&auth_url, None).await?;
-    let status: bool = response.status().is_success();
+    log::info!("User {} authentication was successful.", user.principal());
+    Ok(user)
+}
 
-    let user: User = response.json().await?;
+/// Resolves a principal and every group it belongs to, directly or through nested groups.
+///
+/// The groups of the authenticated user come from the SCIM `/Me` endpoint, any other user is
+/// looked up with a `/Users` filter, falling back to a `/ServicePrincipals` filter on the
+/// application id when no user matches. Parent groups are then followed through `/Groups/{id}`
+/// until no new group is found. Every user is also a member of the `account users` group.
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `principal` - The user name i.e. 'first.last@example.com', or the application id of a service
+///   principal.
+///
+/// # Returns
+///
+/// * `Identity` - The SCIM user name or application id of the principal, or the name as given
+///   if it is not a workspace user or service principal, and the display names of all of its
+///   groups.
+///
+/// # Errors
+///
+/// Returns an `Error` if the API request fails or if the response cannot be parsed.
+///
+/// # Examples
+///
+/// ```ignore
+/// let identity: Identity = resolve_identity(api_client, "first.last@example.com").await?;
+/// assert!(identity.is("data-engineers"));
+/// ```
+pub async fn resolve_identity(api_client: APIClient, principal: &str) -> Result<Identity, Error> {
+    let (user_name, memberships) = walk_groups(&api_client, principal).await?;
+    let mut groups: Vec<String> = Vec::new();
+    for (_, group) in memberships {
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
 
-    // need to properly authenticate that the user matches and the workspace matches. I should call the API using the provided token in the function
+    if !groups.iter().any(|g| g == ACCOUNT_USERS_GROUP) {
+        groups.push(ACCOUNT_USERS_GROUP.to_string());
+    }
+    Ok(Identity { user_name, groups })
+}
 
-    if !status && user.user_name == user_name {
-        log::error!("Failed to authenticate user: {}", user.user_name);
+/// Returns the direct group memberships of a principal and of every group it belongs to,
+/// keyed by member. Unlike `resolve_identity` it keeps the group hierarchy, i.e. to evaluate
+/// access offline after adding a group to another group.
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `principal` - The user name i.e. 'first.last@example.com', or the application id of a service
+///   principal.
+///
+/// # Returns
+///
+/// * `BTreeMap<String, BTreeSet<String>>` - The groups each member directly belongs to, the
+///   principal being keyed by its SCIM user name.
+///
+/// # Errors
+///
+/// Returns an `Error` if the API request fails or if the response cannot be parsed.
+///
+/// # Examples
+///
+/// ```ignore
+/// let memberships = group_memberships(api_client, "first.last@example.com").await?;
+/// assert!(memberships["data-engineers"].contains("data-platform"));
+/// ```
+pub async fn group_memberships(
+    api_client: APIClient,
+    principal: &str,
+) -> Result<BTreeMap<String, BTreeSet<String>>, Error> {
+    let mut memberships: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let (_, pairs) = walk_groups(&api_client, principal).await?;
+    for (member, group) in pairs {
+        memberships.entry(member).or_default().insert(group);
+    }
+    Ok(memberships)
+}
+
+/// Walks up the group hierarchy of a principal breadth first and returns the canonical name of
+/// the principal with every direct membership as a (member, group) pair, in the order the
+/// groups were found.
+async fn walk_groups(
+    api_client: &APIClient,
+    principal: &str,
+) -> Result<(String, Vec<(String, String)>), Error> {
+    let me: User = api_client.get("/api/2.0/preview/scim/v2/Me").await?;
+    let user: Option<User> = if me.is(principal) {
+        Some(me)
     } else {
-        log::info!("User {} authentication was successful.", user.user_name);
+        match find_user(api_client, principal).await? {
+            Some(user) => Some(user),
+            None => find_service_principal(api_client, principal).await?,
+        }
+    };
+
+    let mut memberships: Vec<(String, String)> = Vec::new();
+    // display names of the groups already fetched, by id
+    let mut seen: HashMap<String, String> = HashMap::new();
+    let mut pending: VecDeque<(String, GroupRef)> = VecDeque::new();
+    // the name as stored by SCIM, which grants and owners refer to
+    let user_name: String = match user {
+        Some(user) => {
+            let user_name: String = user.principal().to_string();
+            pending.extend(
+                user.groups
+                    .into_iter()
+                    .map(|group_ref| (user_name.clone(), group_ref)),
+            );
+            user_name
+        }
+        None => {
+            log::warn!(
+                "Principal {} not found in the workspace, group grants are not considered.",
+                principal
+            );
+            principal.to_string()
+        }
+    };
+
+    while let Some((member, group_ref)) = pending.pop_front() {
+        if let Some(display_name) = seen.get(&group_ref.value) {
+            memberships.push((member, display_name.clone()));
+            continue;
+        }
+        let group: Group = api_client
+            .get(&format!(
+                "/api/2.0/preview/scim/v2/Groups/{}",
+                group_ref.value
+            ))
+            .await?;
+        log::debug!("{} is a member of group {}", member, group.display_name);
+        seen.insert(group_ref.value, group.display_name.clone());
+        memberships.push((member, group.display_name.clone()));
+        pending.extend(
+            group
+                .groups
+                .into_iter()
+                .map(|parent| (group.display_name.clone(), parent)),
+        );
     }
+    Ok((user_name, memberships))
+}
+
+/// Looks up a workspace user by user name.
+async fn find_user(api_client: &APIClient, user_name: &str) -> Result<Option<User>, Error> {
+    let mut url: Url = Url::parse(&api_client.url("/api/2.0/preview/scim/v2/Users"))
+        .map_err(|e| Error::Config(format!("invalid workspace URL: {}", e)))?;
+    url.query_pairs_mut()
+        .append_pair("filter", &scim_filter("userName", user_name));
+    let response: ScimListResponse<User> = api_client.get(url.as_str()).await?;
+    Ok(response.resources.into_iter().next())
+}
 
-    Ok(status)
+/// Looks up a workspace service principal by application id.
+async fn find_service_principal(
+    api_client: &APIClient,
+    application_id: &str,
+) -> Result<Option<User>, Error> {
+    let mut url: Url = Url::parse(&api_client.url("/api/2.0/preview/scim/v2/ServicePrincipals"))
+        .map_err(|e| Error::Config(format!("invalid workspace URL: {}", e)))?;
+    url.query_pairs_mut()
+        .append_pair("filter", &scim_filter("applicationId", application_id));
+    let response: ScimListResponse<User> = api_client.get(url.as_str()).await?;
+    Ok(response.resources.into_iter().next())
 }
 
-// /api/2.1/unity-catalog/permissions/{securable_type}/{full_name}
-/// Fetches permissions for a given object from Unity Catalog API.
+/// Builds a SCIM filter matching an attribute exactly, escaping `\` and `"` in the value so a
+/// principal cannot change the filter expression.
+fn scim_filter(attribute: &str, value: &str) -> String {
+    format!(
+        "{} eq \"{}\"",
+        attribute,
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Fetches the effective permissions on an object from the Unity Catalog API.
+/// Effective permissions include privileges inherited from parent objects, i.e. a `SELECT`
+/// granted on a catalog is returned for every table of the catalog together with its source.
+/// Reference: /api/2.1/unity-catalog/effective-permissions/{securable_type}/{full_name}
 ///
 /// # Arguments
 ///
 /// * `api_client` - API client object for making HTTP requests.
 /// * `securable_type` - Type of securable object (e.g., Catalog, Schema, Table).
 /// * `full_name` - Fully qualified name of the object.
-/// * `principal` - The principal user or group for which permissions are being fetched.
+/// * `principal` - Optional user or group to restrict the result to. All principals are returned when `None`.
 ///
 /// # Returns
 ///
-/// * `PrivilegeAssignmentsResponse` - Response containing the permissions assignments for the object.
+/// * `EffectivePrivileges` - The privileges per principal and where each one is inherited from.
 ///
 /// # Errors
 ///
 /// Returns an `Error` if the API request fails or if the response cannot be parsed.
-async fn fetch_permissions(
+///
+/// # Examples
+///
+/// ```ignore
+/// let privileges: EffectivePrivileges = get_effective_permissions(api_client, SecurableType::Table, "main.sales.orders", Some("first.last@example.com")).await?;
+/// if let Some(select) = privileges.find("first.last@example.com", Privilege::Select) {
+///     println!("SELECT inherited from {:?}", select.inherited_from_name);
+/// }
+/// ```
+pub async fn get_effective_permissions(
     api_client: APIClient,
     securable_type: SecurableType,
     full_name: &str,
-    principal: &str,
-) -> Result<PrivilegeAssignmentsResponse, Error> {
-    // Determine the string representation of the securable type
-    let securable_type_str = securable_type.to_string();
+    principal: Option<&str>,
+) -> Result<EffectivePrivileges, Error> {
+    let mut url: Url = Url::parse(&api_client.url(&format!(
+        "/api/2.1/unity-catalog/effective-permissions/{}/{}",
+        securable_type, full_name
+    )))
+    .map_err(|e| Error::Config(format!("invalid workspace URL: {}", e)))?;
+    if let Some(principal) = principal {
+        url.query_pairs_mut().append_pair("principal", principal);
+    }
+    log::info!("Getting Effective Permissions - {}", url);
 
-    // Split full name and make API calls to fetch permissions for each part
-    let name_parts: Vec<&str> = full_name.split('.').collect();
-    let catalog_name = name_parts.get(0).unwrap_or(&"").trim_matches('"'); // Always expect a catalog
-    let schema_name = match (name_parts.get(0), name_parts.get(1)) {
-        (Some(part1), Some(part2)) => format!("{}.{}", part1, part2),
-        _ => "".to_string(), // Handle case where parts are missing
-    };
+    let response: EffectivePermissionsResponse = api_client.get(url.as_str()).await?;
+    Ok(EffectivePrivileges {
+        securable_type,
+        full_name: full_name.to_string(),
+        assignments: response.privilege_assignments.unwrap_or_default(),
+    })
+}
 
-    let mut privileges: PrivilegeAssignmentsResponse = PrivilegeAssignmentsResponse::new();
+/// Fetches the privileges granted directly on an object from the Unity Catalog API.
+/// Unlike `get_effective_permissions`, privileges inherited from parent objects are not included.
+/// Reference: /api/2.1/unity-catalog/permissions/{securable_type}/{full_name}
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `securable_type` - Type of securable object (e.g., Catalog, Schema, Table).
+/// * `full_name` - Fully qualified name of the object.
+///
+/// # Returns
+///
+/// * `PrivilegeAssignmentsResponse` - The direct grants on the object per principal.
+///
+/// # Errors
+///
+/// Returns an `Error` if the API request fails or if the response cannot be parsed.
+pub async fn get_permissions(
+    api_client: APIClient,
+    securable_type: SecurableType,
+    full_name: &str,
+) -> Result<PrivilegeAssignmentsResponse, Error> {
+    let path: String = format!(
+        "/api/2.1/unity-catalog/permissions/{}/{}",
+        securable_type, full_name
+    );
+    log::info!("Getting Permissions - {}", path);
 
-    // Fetch permissions for catalog
-    if !name_parts.get(0).is_none() {
-        let catalog_auth_url: String = format!(
-            "https://{}/api/2.1/unity-catalog/permissions/{}/{}?principal={}",
-            api_client.workspace_name, "catalog", catalog_name, principal
-        );
-        log::info!("Getting Catalog Permissions - {}", catalog_auth_url);
-        let catalog_response: Response = api_client.fetch(&catalog_auth_url, None).await?;
-        let catalog_perms: PrivilegeAssignmentsResponse = catalog_response.json().await?;
-        privileges.add_assignment(catalog_perms, &catalog_name, SecurableType::Catalog);
-    }
+    let response: PrivilegeAssignmentsResponse = api_client.get(&path).await?;
+    let mut grants: PrivilegeAssignmentsResponse = PrivilegeAssignmentsResponse::new();
+    grants.add_assignment(response, full_name, securable_type);
+    Ok(grants)
+}
 
-    // Fetch permissions for schema
-    if !name_parts.get(1).is_none() {
-        let schema_auth_url: String = format!(
-            "https://{}/api/2.1/unity-catalog/permissions/{}/{}?principal={}",
-            api_client.workspace_name, "schema", schema_name, principal
+/// Grants and revokes privileges on an object through the Unity Catalog API.
+/// Changes for several principals are applied in a single request. Granting a privilege the
+/// principal already holds, or revoking one it does not hold, is not an error.
+/// Reference: PATCH /api/2.1/unity-catalog/permissions/{securable_type}/{full_name}
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `securable_type` - Type of securable object (e.g., Catalog, Schema, Table).
+/// * `full_name` - Fully qualified name of the object.
+/// * `changes` - The privileges to add and remove per principal.
+///
+/// # Returns
+///
+/// * `PrivilegeAssignmentsResponse` - The direct grants on the object after the update.
+///
+/// # Errors
+///
+/// Returns an `Error` if the API request fails, i.e. when the caller is not allowed to manage
+/// grants on the object, or if the response cannot be parsed.
+///
+/// # Examples
+///
+/// ```ignore
+/// let changes: Vec<PermissionsChange> = vec![
+///     PermissionsChange::new("data-engineers").grant(&[Privilege::Select, Privilege::Modify]),
+///     PermissionsChange::new("first.last@example.com").revoke(&[Privilege::Modify]),
+/// ];
+/// let grants: PrivilegeAssignmentsResponse = update_permissions(api_client, SecurableType::Table, "main.sales.orders", &changes).await?;
+/// ```
+pub async fn update_permissions(
+    api_client: APIClient,
+    securable_type: SecurableType,
+    full_name: &str,
+    changes: &[PermissionsChange],
+) -> Result<PrivilegeAssignmentsResponse, Error> {
+    for change in changes {
+        log::info!(
+            "Updating permissions on {} {} for {} - add: {:?}, remove: {:?}",
+            securable_type,
+            full_name,
+            change.principal,
+            change.add,
+            change.remove
         );
-        log::info!("Getting Schema Permissions - '{}'", schema_auth_url);
-        let schema_response: Response = api_client.fetch(&schema_auth_url, None).await?;
-        let schema_perms: PrivilegeAssignmentsResponse = schema_response.json().await?;
-        privileges.add_assignment(schema_perms, &schema_name, SecurableType::Schema);
     }
 
-    // Fetch permissions for the object itself
-    if !name_parts.get(2).is_none() {
-        let obj_auth_url: String = format!(
-            "https://{}/api/2.1/unity-catalog/permissions/{}/{}?principal={}",
-            api_client.workspace_name, &securable_type_str, full_name, principal
-        );
-        log::info!("Getting Object Permissions - {}", obj_auth_url);
-        let obj_response: Response = api_client.fetch(&obj_auth_url, None).await?;
-        let obj_perms: PrivilegeAssignmentsResponse = obj_response.json().await?;
-        privileges.add_assignment(obj_perms, &full_name, SecurableType::Table);
-    }
+    let path: String = format!(
+        "/api/2.1/unity-catalog/permissions/{}/{}",
+        securable_type, full_name
+    );
+    let body: UpdatePermissionsRequest = UpdatePermissionsRequest { changes };
+    let response: PrivilegeAssignmentsResponse = api_client
+        .patch(&path, &body)
+        .await
+        .inspect_err(|_| log::error!("Failed to update permissions on {}", full_name))?;
 
-    Ok(privileges)
+    let mut grants: PrivilegeAssignmentsResponse = PrivilegeAssignmentsResponse::new();
+    grants.add_assignment(response, full_name, securable_type);
+    Ok(grants)
 }
 
 /// Fetches the owner of a specified object from Unity Catalog API.
@@ -134,188 +422,250 @@ async fn get_object_owner(
     securable_type: SecurableType,
     full_name: &str,
 ) -> Result<ObjectOwnerResponse, Error> {
-    log::info!(
-        "Checking ownership on {}: {}",
-        securable_type.to_string(),
-        full_name
-    );
+    log::info!("Checking ownership on {}: {}", securable_type, full_name);
 
-    let url: String = format!(
-        "https://{}/api/2.1/unity-catalog/{}s/{}",
-        api_client.workspace_name,
-        securable_type.to_string(),
-        full_name
-    );
-    let response: Response = api_client.fetch(&url, None).await?;
-    let status: bool = response.status().is_success();
+    let url: String = api_client.url(&format!(
+        "/api/2.1/unity-catalog/{}s/{}",
+        securable_type, full_name
+    ));
+    let response: Response = api_client
+        .fetch(&url, None)
+        .await
+        .inspect_err(|_| log::error!("Failed to get owner of object - {}", full_name))?;
     let owner_response: ObjectOwnerResponse = response.json().await?;
 
-    if !status {
-        log::error!("Failed to get owner of object - {}", full_name);
-    }
-
     Ok(owner_response)
 }
 
-/// Checks if a principal has the specified permissions on a given object.
+/// Evaluates whether a principal may perform an operation on an object, following the Unity
+/// Catalog privilege model implemented by `PrivilegeEvaluator`.
 ///
 /// # Arguments
 ///
 /// * `api_client` - API client object for making HTTP requests.
 /// * `securable_type` - Type of securable object (e.g., Catalog, Schema, Table).
 /// * `full_name` - Fully qualified name of the object.
-/// * `principal` - The principal user or group for which permissions are being checked.
-/// * `permissions` - Vector of permissions to check against.
+/// * `principal` - The principal user for which permissions are being checked.
+/// * `operation` - The operation to evaluate.
 ///
 /// # Returns
 ///
-/// * `bool` - `true` if the principal has the specified permissions, `false` otherwise.
+/// * `Decision` - Whether the operation is allowed and every requirement that was checked.
 ///
 /// # Errors
 ///
 /// Returns an `Error` if the API request fails or if the response cannot be parsed.
-async fn check_permissions(
+///
+/// # Examples
+///
+/// ```ignore
+/// let decision: Decision = evaluate_access(api_client, SecurableType::Table, "main.sales.orders", "first.last@example.com", Operation::Read).await?;
+/// ```
+pub async fn evaluate_access(
     api_client: APIClient,
     securable_type: SecurableType,
     full_name: &str,
     principal: &str,
-    permissions: Vec<&str>,
-) -> Result<bool, Error> {
-    let mut perm_check: bool = false; // deny by default
-    let object_permissions: PrivilegeAssignmentsResponse = fetch_permissions(
-        api_client.clone(),
-        securable_type.clone(),
+    operation: Operation,
+) -> Result<Decision, Error> {
+    // grants and ownership held by any group of the principal count as well.
+    // The identity and the hierarchy are independent, so they are fetched concurrently.
+    let (identity, levels): (Identity, Vec<SecurableFacts>) = futures::try_join!(
+        resolve_identity(api_client.clone(), principal),
+        load_hierarchy(api_client, securable_type, full_name)
+    )?;
+    let decision: Decision = PrivilegeEvaluator::new(&identity, &levels).evaluate(operation);
+
+    for check in &decision.checks {
+        match &check.satisfied_by {
+            Some(source) => log::info!(
+                "Principal {} has {} on {}: {}.",
+                principal,
+                check.privilege,
+                check.securable,
+                source
+            ),
+            None => log::info!(
+                "Principal {} is missing {} on {}.",
+                principal,
+                check.privilege,
+                check.securable
+            ),
+        }
+    }
+    log::info!(
+        "{:?} on {} {} for principal {}.",
+        operation,
         full_name,
-        principal,
-    )
-    .await?;
+        if decision.allowed {
+            "allowed"
+        } else {
+            "denied"
+        },
+        principal
+    );
+    Ok(decision)
+}
+
+/// Fetches the owner and effective permissions of an object and each of its parents,
+/// ordered from the catalog down to the object. All lookups run concurrently and the first
+/// failure fails the whole hierarchy, so a partial hierarchy never reaches the evaluator.
+async fn load_hierarchy(
+    api_client: APIClient,
+    securable_type: SecurableType,
+    full_name: &str,
+) -> Result<Vec<SecurableFacts>, Error> {
+    let levels = hierarchy(securable_type, full_name).into_iter().map(|(securable_type, name)| {
+        let api_client: APIClient = api_client.clone();
+        async move {
+            let (owner, privileges): (ObjectOwnerResponse, EffectivePrivileges) = futures::try_join!(
+                get_object_owner(api_client.clone(), securable_type.clone(), &name),
+                get_effective_permissions(api_client.clone(), securable_type.clone(), &name, None)
+            )?;
+            Ok::<SecurableFacts, Error>(SecurableFacts {
+                securable_type,
+                full_name: name,
+                owner: Some(owner.owner),
+                privileges,
+            })
+        }
+    });
+    // try_join_all keeps the order of the levels
+    futures::future::try_join_all(levels).await
+}
 
-    // split full name and make 3 different api calls since permissions can be delagated
+/// Returns an object and its parents ordered from the catalog down to the object,
+/// i.e. `main`, `main.sales`, `main.sales.orders` for a table.
+pub(crate) fn hierarchy(
+    securable_type: SecurableType,
+    full_name: &str,
+) -> Vec<(SecurableType, String)> {
+    // only schemas and the objects inside them have parents, i.e. external locations have none
     let name_parts: Vec<&str> = full_name.split('.').collect();
-    let catalog_name = name_parts.get(0).unwrap().trim_matches('"'); // always expect a catalog   - ok_or("Error Getting Catalog")?
-    let schema_name = match (name_parts.get(0), name_parts.get(1)) {
-        // may not always be a schema
-        (Some(part1), Some(part2)) => format!("{}.{}", part1, part2),
-        _ => "".to_string(), // handle case where parts are missing
+    let depth: usize = match securable_type {
+        SecurableType::Schema => 1,
+        SecurableType::Table | SecurableType::Volume | SecurableType::Function => 2,
+        _ => 0,
     };
-
-    // if they are an owner of the object or one of the parent objects then we return TRUE
-    // start with the object and go higher b/c its more likely users are owners of tables than catalogs
-    if get_object_owner(api_client.clone(), securable_type.clone(), full_name)
-        .await?
-        .owner
-        == principal
-    {
-        log::info!("Princpal {} is an owner of {}. ", principal, full_name);
-        perm_check = true;
-    } else if !name_parts.get(1).is_none()
-        && get_object_owner(api_client.clone(), SecurableType::Schema, &schema_name)
-            .await?
-            .owner
-            == principal
-    {
-        // if princpal is the owner of the schema then return True
-        log::info!("Princpal {} is an owner of {}. ", principal, schema_name);
-        perm_check = true;
-    } else if !name_parts.get(0).is_none()
-        && get_object_owner(api_client.clone(), SecurableType::Catalog, &catalog_name)
-            .await?
-            .owner
-            == principal
-    {
-        // if princpal is the owner of the catalog then return True
-        log::info!("Princpal {} is an owner of {}. ", principal, catalog_name);
-        perm_check = true;
-    }
-    // if there are permissions on the object to review
-    else if let Some(assigns) = object_permissions.privilege_assignments {
-        log::info!(
-            "Princpal {} not an owner of {} or any parent object. ",
-            principal,
-            full_name
-        );
-        for s in assigns {
-            // we do not need to verify principal as we only get permissions for that principal
-            // but do want to log the princpal we are mapping the current user to i.e. if they are part of a group
-            if let Some(p) = &s.principal {
-                if let Some(pp) = s.privileges {
-                    for value in pp {
-                        // if the value is in the read_list vec then return TRUE
-                        if permissions.contains(&value.as_str()) {
-                            log::info!(
-                                "Principal {} has {} permissions on {}.",
-                                p,
-                                value,
-                                s.object_name
-                            );
-                            perm_check = true;
-                        }
-                    }
-                }
-            }
-        }
+    let mut objects: Vec<(SecurableType, String)> = Vec::new();
+    if depth > 0 && name_parts.len() > 1 {
+        objects.push((SecurableType::Catalog, name_parts[0].to_string()));
     }
-    Ok(perm_check)
+    if depth > 1 && name_parts.len() > 2 {
+        objects.push((SecurableType::Schema, name_parts[..2].join(".")));
+    }
+    objects.push((securable_type, full_name.to_string()));
+    objects
 }
 
-/// Checks if a principal can read a given object from Unity Catalog API.
+/// Checks if a principal can exercise a privilege on any Unity Catalog object.
+/// Objects inside a catalog additionally require `USE_CATALOG` on the catalog and `USE_SCHEMA`
+/// on the schema, i.e. `READ_VOLUME` on `main.raw.landing` requires `USE_CATALOG` on `main`
+/// and `USE_SCHEMA` on `main.raw`.
 ///
 /// # Arguments
 ///
 /// * `api_client` - API client object for making HTTP requests.
-/// * `full_name` - Fully qualified name of the object.
-/// * `principal` - The principal user or group for which permissions are being checked.
+/// * `principal` - The principal user for which permissions are being checked.
+/// * `privilege` - The privilege to check.
+/// * `securable_type` - Type of securable object (e.g., Volume, Function, ExternalLocation).
+/// * `full_name` - Fully qualified name of the object, or the name of objects outside of catalogs.
 ///
 /// # Returns
 ///
-/// * `bool` - `true` if the principal can read the object, `false` otherwise.
+/// * `bool` - `true` if the principal holds the privilege, `false` otherwise.
 ///
 /// # Errors
 ///
 /// Returns an `Error` if the API request fails or if the response cannot be parsed.
-pub async fn can_read(
+///
+/// # Examples
+///
+/// ```ignore
+/// let can_execute: bool = check(api_client, "first.last@example.com", Privilege::Execute, SecurableType::Function, "main.default.mask_ssn").await?;
+/// ```
+pub async fn check(
     api_client: APIClient,
-    full_name: &str,
     principal: &str,
+    privilege: Privilege,
+    securable_type: SecurableType,
+    full_name: &str,
 ) -> Result<bool, Error> {
-    let readable_permissions = vec!["SELECT", "ALL_PRIVILEGES"];
-    let securable_type: SecurableType = SecurableType::Table;
-
-    // split full name and make 3 different api calls since permissions can be delagated
-    let name_parts: Vec<&str> = full_name.split('.').collect();
-    let catalog_name = name_parts.get(0).unwrap().trim_matches('"'); // always expect a catalog
-    let schema_name = match (name_parts.get(0), name_parts.get(1)) {
-        // may not always be a schema
-        (Some(part1), Some(part2)) => format!("{}.{}", part1, part2),
-        _ => "".to_string(), // handle case where parts are missing
-    };
     log::info!(
-        "Checking if {} can read the following objects: {} | {} | {}",
+        "Checking if {} has {} on {} {}",
         principal,
-        catalog_name,
-        schema_name,
+        privilege,
+        securable_type,
         full_name
     );
-
-    let readable: bool = check_permissions(
+    let decision: Decision = evaluate_access(
         api_client,
         securable_type,
         full_name,
         principal,
-        readable_permissions,
+        Operation::Privilege(privilege),
     )
-    .await?; // deny by default
+    .await?;
+    Ok(decision.allowed)
+}
 
-    Ok(readable)
+/// Explains why a principal holds a privilege on a Unity Catalog object or not.
+/// The explanation lists ownership and the grants of the principal and its groups on each level,
+/// every requirement checked, the missing ones and the verdict, i.e. for support requests.
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `principal` - The principal user for which permissions are being explained.
+/// * `privilege` - The privilege to explain.
+/// * `securable_type` - Type of securable object (e.g., Table, Volume, Function).
+/// * `full_name` - Fully qualified name of the object, or the name of objects outside of catalogs.
+///
+/// # Returns
+///
+/// * `AccessExplanation` - The decision trace, serializable to JSON.
+///
+/// # Errors
+///
+/// Returns an `Error` if the API request fails or if the response cannot be parsed.
+///
+/// # Examples
+///
+/// ```ignore
+/// let explanation: AccessExplanation = explain_access(api_client, "first.last@example.com", Privilege::Select, SecurableType::Table, "main.sales.orders").await?;
+/// println!("{}", serde_json::to_string_pretty(&explanation)?);
+/// ```
+pub async fn explain_access(
+    api_client: APIClient,
+    principal: &str,
+    privilege: Privilege,
+    securable_type: SecurableType,
+    full_name: &str,
+) -> Result<AccessExplanation, Error> {
+    let (identity, levels): (Identity, Vec<SecurableFacts>) = futures::try_join!(
+        resolve_identity(api_client.clone(), principal),
+        load_hierarchy(api_client, securable_type, full_name)
+    )?;
+    let explanation: AccessExplanation =
+        PrivilegeEvaluator::new(&identity, &levels).explain(privilege);
+    log::info!(
+        "{} on {} for principal {}: {}.",
+        privilege,
+        full_name,
+        principal,
+        explanation.verdict
+    );
+    Ok(explanation)
 }
 
-/// Checks if a principal can modify a given object from Unity Catalog API.
+/// Checks if a principal can read a given object from Unity Catalog API.
+/// Requires `USE_CATALOG` on the catalog, `USE_SCHEMA` on the schema and `SELECT` on the object.
 ///
 /// # Arguments
 ///
 /// * `api_client` - API client object for making HTTP requests.
 /// * `full_name` - Fully qualified name of the object.
-/// * `principal` - The principal user or group for which permissions are being checked.
+/// * `principal` - The principal user for which permissions are being checked.
 ///
 /// # Returns
 ///
@@ -324,41 +674,87 @@ pub async fn can_read(
 /// # Errors
 ///
 /// Returns an `Error` if the API request fails or if the response cannot be parsed.
+pub async fn can_read(
+    api_client: APIClient,
+    full_name: &str,
+    principal: &str,
+) -> Result<bool, Error> {
+    log::info!("Checking if {} can read {}", principal, full_name);
+    let decision: Decision = evaluate_access(
+        api_client,
+        SecurableType::Table,
+        full_name,
+        principal,
+        Operation::Read,
+    )
+    .await?;
+    Ok(decision.allowed)
+}
 
+/// Checks if a principal can modify a given object from Unity Catalog API.
+/// Requires `USE_CATALOG` on the catalog, `USE_SCHEMA` on the schema and `MODIFY` on the object.
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `full_name` - Fully qualified name of the object.
+/// * `principal` - The principal user for which permissions are being checked.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the principal can modify the object, `false` otherwise.
+///
+/// # Errors
+///
+/// Returns an `Error` if the API request fails or if the response cannot be parsed.
 pub async fn can_write(
     api_client: APIClient,
     full_name: &str,
     principal: &str,
 ) -> Result<bool, Error> {
-    let writable_permissions: Vec<&str> = vec!["MODIFY", "ALL_PRIVILEGES"];
-    let securable_type: SecurableType = SecurableType::Table;
-
-    // split full name and make 3 different api calls since permissions can be delagated
-    let name_parts: Vec<&str> = full_name.split('.').collect();
-    let catalog_name = name_parts.get(0).unwrap().trim_matches('"'); // always expect a catalog
-    let schema_name = match (name_parts.get(0), name_parts.get(1)) {
-        // may not always be a schema
-        (Some(part1), Some(part2)) => format!("{}.{}", part1, part2),
-        _ => "".to_string(), // handle case where parts are missing
-    };
-    log::info!(
-        "Checking if {} can read the following objects: {} | {} | {}",
+    log::info!("Checking if {} can write {}", principal, full_name);
+    let decision: Decision = evaluate_access(
+        api_client,
+        SecurableType::Table,
+        full_name,
         principal,
-        catalog_name,
-        schema_name,
-        full_name
-    );
+        Operation::Write,
+    )
+    .await?;
+    Ok(decision.allowed)
+}
 
-    let writable: bool = check_permissions(
+/// Checks if a principal can see the metadata of a given object from Unity Catalog API.
+/// Requires `BROWSE`, or any privilege on the object together with `USE_CATALOG` and `USE_SCHEMA`.
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `full_name` - Fully qualified name of the object.
+/// * `principal` - The principal user for which permissions are being checked.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the principal can browse the object, `false` otherwise.
+///
+/// # Errors
+///
+/// Returns an `Error` if the API request fails or if the response cannot be parsed.
+pub async fn can_browse(
+    api_client: APIClient,
+    full_name: &str,
+    principal: &str,
+) -> Result<bool, Error> {
+    log::info!("Checking if {} can browse {}", principal, full_name);
+    let decision: Decision = evaluate_access(
         api_client,
-        securable_type,
+        SecurableType::Table,
         full_name,
         principal,
-        writable_permissions,
+        Operation::Browse,
     )
-    .await?; // deny by default
-
-    Ok(writable)
+    .await?;
+    Ok(decision.allowed)
 }
 
 // wrapper struct to hold all permissions on an object
@@ -366,6 +762,12 @@ pub async fn can_write(
 pub struct PrivilegeAssignmentsResponse {
     pub privilege_assignments: Option<Vec<PrivilegeAssignment>>,
 }
+impl Default for PrivilegeAssignmentsResponse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PrivilegeAssignmentsResponse {
     // Constructor to create an empty PrivilegeAssignmentsResponse
     pub fn new() -> Self {
@@ -394,6 +796,124 @@ impl PrivilegeAssignmentsResponse {
     }
 }
 
+/// Privileges to grant to and revoke from a single principal.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PermissionsChange {
+    /// The user, service principal or group the change applies to.
+    pub principal: String,
+    /// Privileges to grant.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub add: Vec<Privilege>,
+    /// Privileges to revoke.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<Privilege>,
+}
+
+impl PermissionsChange {
+    /// Creates a change for a principal that neither grants nor revokes anything yet.
+    pub fn new(principal: &str) -> Self {
+        PermissionsChange {
+            principal: principal.to_string(),
+            add: Vec::new(),
+            remove: Vec::new(),
+        }
+    }
+
+    /// Adds privileges to grant.
+    pub fn grant(mut self, privileges: &[Privilege]) -> Self {
+        self.add.extend_from_slice(privileges);
+        self
+    }
+
+    /// Adds privileges to revoke.
+    pub fn revoke(mut self, privileges: &[Privilege]) -> Self {
+        self.remove.extend_from_slice(privileges);
+        self
+    }
+}
+
+// request body of the update permissions endpoint
+#[derive(Debug, Serialize)]
+struct UpdatePermissionsRequest<'a> {
+    changes: &'a [PermissionsChange],
+}
+
+/// Effective privileges on an object, including privileges inherited from its parents.
+#[derive(Debug, Clone)]
+pub struct EffectivePrivileges {
+    /// Type of the object the privileges apply to.
+    pub securable_type: SecurableType,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// The privileges held by each principal.
+    pub assignments: Vec<EffectivePrivilegeAssignment>,
+}
+
+impl EffectivePrivileges {
+    /// Returns the privileges held by a principal.
+    pub fn privileges_for<'a>(
+        &'a self,
+        principal: &'a str,
+    ) -> impl Iterator<Item = &'a EffectivePrivilege> + 'a {
+        self.assignments
+            .iter()
+            .filter(move |assignment| assignment.principal.eq_ignore_ascii_case(principal))
+            .flat_map(|assignment| assignment.privileges.iter())
+    }
+
+    /// Returns the grant of a privilege to a principal, if the principal holds it.
+    ///
+    /// # Arguments
+    ///
+    /// * `principal` - The user or group.
+    /// * `privilege` - The privilege i.e. `Privilege::Select`.
+    pub fn find(&self, principal: &str, privilege: Privilege) -> Option<&EffectivePrivilege> {
+        self.assignments
+            .iter()
+            .filter(|assignment| assignment.principal.eq_ignore_ascii_case(principal))
+            .flat_map(|assignment| assignment.privileges.iter())
+            .find(|p| p.privilege.eq_ignore_ascii_case(privilege.as_str()))
+    }
+
+    /// Returns true if any of the principals holds any of the privileges.
+    pub fn has_any(&self, principals: &[&str], privileges: &[Privilege]) -> bool {
+        principals.iter().any(|principal| {
+            privileges
+                .iter()
+                .any(|privilege| self.find(principal, *privilege).is_some())
+        })
+    }
+}
+
+// wrapper struct for the effective permissions endpoint
+#[derive(Debug, Deserialize, Clone)]
+struct EffectivePermissionsResponse {
+    privilege_assignments: Option<Vec<EffectivePrivilegeAssignment>>,
+}
+
+// the effective privileges of a single principal
+#[derive(Debug, Deserialize, Clone)]
+pub struct EffectivePrivilegeAssignment {
+    pub principal: String,
+    #[serde(default)]
+    pub privileges: Vec<EffectivePrivilege>,
+}
+
+// a privilege and the object it is inherited from, both `inherited_from` fields are empty for direct grants
+#[derive(Debug, Deserialize, Clone)]
+pub struct EffectivePrivilege {
+    pub privilege: String,
+    pub inherited_from_type: Option<SecurableType>,
+    pub inherited_from_name: Option<String>,
+}
+
+impl EffectivePrivilege {
+    /// Returns true if the privilege is inherited from a parent object rather than granted directly.
+    pub fn is_inherited(&self) -> bool {
+        self.inherited_from_name.is_some()
+    }
+}
+
 // struct to old ownership information
 #[derive(Debug, Deserialize, Clone)]
 pub struct ObjectOwnerResponse {
@@ -402,7 +922,7 @@ pub struct ObjectOwnerResponse {
 }
 
 // Struct to represent the privilege assignment objects in UC.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PrivilegeAssignment {
     #[serde(skip)]
     pub object_name: String,
@@ -412,18 +932,99 @@ pub struct PrivilegeAssignment {
 }
 
 // A struct to represent the user object returned by the authentication endpoint
+// service principals are identified by `application_id` and may not have a user name
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
     pub id: String,
+    #[serde(default)]
     pub user_name: String,
+    pub application_id: Option<String>,
+    #[serde(default)]
     pub display_name: String,
     pub active: bool,
+    #[serde(default)]
+    pub groups: Vec<GroupRef>,
+}
+
+impl User {
+    /// Returns true if this is a service principal rather than a workspace user.
+    pub fn is_service_principal(&self) -> bool {
+        self.application_id.is_some()
+    }
+
+    /// The name grants refer to: the application id of a service principal, the user name otherwise.
+    pub fn principal(&self) -> &str {
+        self.application_id.as_deref().unwrap_or(&self.user_name)
+    }
+
+    /// Returns true if the name is this user's user name, ignoring case, or its application id.
+    pub fn is(&self, name: &str) -> bool {
+        (!self.user_name.is_empty() && self.user_name.eq_ignore_ascii_case(name))
+            || self.application_id.as_deref() == Some(name)
+    }
+}
+
+// reference to a group in a SCIM user or group, `value` holds the group id
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupRef {
+    pub display: Option<String>,
+    pub value: String,
+    #[serde(rename = "type")]
+    pub membership_type: Option<String>,
+}
+
+// A SCIM group, `groups` lists the groups this group is a member of
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Group {
+    pub id: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub groups: Vec<GroupRef>,
+}
+
+// wrapper struct for SCIM list responses
+#[derive(Debug, Deserialize, Clone)]
+struct ScimListResponse<T> {
+    #[serde(rename = "Resources", default = "Vec::new")]
+    resources: Vec<T>,
+}
+
+/// Group every account user implicitly belongs to.
+pub const ACCOUNT_USERS_GROUP: &str = "account users";
+
+/// A principal together with the display names of its direct and nested groups.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identity {
+    /// The user name of the principal as stored by SCIM.
+    pub user_name: String,
+    /// Display names of every group the principal belongs to.
+    pub groups: Vec<String>,
+}
+
+impl Identity {
+    /// Returns true if the name is the principal itself or one of its groups, ignoring case.
+    pub fn is(&self, name: &str) -> bool {
+        self.user_name.eq_ignore_ascii_case(name)
+            || self
+                .groups
+                .iter()
+                .any(|group| group.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the principal followed by its groups.
+    pub fn names(&self) -> Vec<&str> {
+        std::iter::once(self.user_name.as_str())
+            .chain(self.groups.iter().map(String::as_str))
+            .collect()
+    }
 }
 
 /// Enum representing various Unity Catalog (UC) securable objects
 /// Refer to: https://docs.databricks.com/en/data-governance/unity-catalog/manage-privileges/privileges.html
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SecurableType {
     Catalog,           // metastore ownership
     Schema,            // catalog ownership
@@ -460,22 +1061,207 @@ impl std::str::FromStr for SecurableType {
         }
     }
 }
-impl ToString for SecurableType {
+impl std::fmt::Display for SecurableType {
     /// Converts a `SecurableType` enum to a string
-    fn to_string(&self) -> String {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value: &str = match self {
+            SecurableType::Catalog => "catalog",
+            SecurableType::Schema => "schema",
+            SecurableType::Table => "table",
+            SecurableType::StorageCredential => "storage_credential",
+            SecurableType::ExternalLocation => "external_location",
+            SecurableType::Function => "function",
+            SecurableType::Share => "share",
+            SecurableType::Provider => "provider",
+            SecurableType::Recipient => "recipient",
+            SecurableType::Metastore => "metastore",
+            SecurableType::Volume => "volume",
+            SecurableType::Connection => "connection",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+/// Enum representing the Unity Catalog (UC) privileges that can be granted on securable objects
+/// Refer to: https://docs.databricks.com/en/data-governance/unity-catalog/manage-privileges/privileges.html
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Privilege {
+    Access,                  // service credential
+    AllPrivileges,           // every privilege on the object and its children
+    ApplyTag,                // catalog, schema, table, volume, function
+    Browse,                  // catalog, external location - metadata only
+    CreateCatalog,           // metastore
+    CreateCleanRoom,         // metastore
+    CreateConnection,        // metastore
+    CreateExternalLocation,  // metastore, storage credential
+    CreateExternalTable,     // external location, storage credential
+    CreateExternalVolume,    // external location
+    CreateForeignCatalog,    // connection
+    CreateForeignSecurable,  // external location
+    CreateFunction,          // catalog, schema
+    CreateManagedStorage,    // external location
+    CreateMaterializedView,  // catalog, schema
+    CreateModel,             // catalog, schema
+    CreateProvider,          // metastore
+    CreateRecipient,         // metastore
+    CreateSchema,            // catalog
+    CreateServiceCredential, // metastore
+    CreateShare,             // metastore
+    CreateStorageCredential, // metastore
+    CreateTable,             // catalog, schema
+    CreateVolume,            // catalog, schema
+    Execute,                 // catalog, schema, function
+    ExecuteCleanRoomTask,    // clean room
+    ExternalUseSchema,       // schema - credential vending to external engines
+    Manage,                  // any securable
+    ManageAllowlist,         // metastore
+    Modify,                  // catalog, schema, table
+    ModifyCleanRoom,         // clean room
+    ReadFiles,               // external location, storage credential
+    ReadVolume,              // catalog, schema, volume
+    Refresh,                 // catalog, schema, materialized view
+    Select,                  // catalog, schema, table, view, share
+    SetSharePermission,      // metastore
+    UseCatalog,              // catalog
+    UseConnection,           // connection
+    UseMarketplaceAssets,    // metastore
+    UseProvider,             // metastore
+    UseRecipient,            // metastore
+    UseSchema,               // catalog, schema
+    UseShare,                // metastore
+    WriteFiles,              // external location, storage credential
+    WriteVolume,             // catalog, schema, volume
+}
+
+impl Privilege {
+    /// Every privilege, in alphabetical order.
+    pub const ALL: [Privilege; 45] = [
+        Privilege::Access,
+        Privilege::AllPrivileges,
+        Privilege::ApplyTag,
+        Privilege::Browse,
+        Privilege::CreateCatalog,
+        Privilege::CreateCleanRoom,
+        Privilege::CreateConnection,
+        Privilege::CreateExternalLocation,
+        Privilege::CreateExternalTable,
+        Privilege::CreateExternalVolume,
+        Privilege::CreateForeignCatalog,
+        Privilege::CreateForeignSecurable,
+        Privilege::CreateFunction,
+        Privilege::CreateManagedStorage,
+        Privilege::CreateMaterializedView,
+        Privilege::CreateModel,
+        Privilege::CreateProvider,
+        Privilege::CreateRecipient,
+        Privilege::CreateSchema,
+        Privilege::CreateServiceCredential,
+        Privilege::CreateShare,
+        Privilege::CreateStorageCredential,
+        Privilege::CreateTable,
+        Privilege::CreateVolume,
+        Privilege::Execute,
+        Privilege::ExecuteCleanRoomTask,
+        Privilege::ExternalUseSchema,
+        Privilege::Manage,
+        Privilege::ManageAllowlist,
+        Privilege::Modify,
+        Privilege::ModifyCleanRoom,
+        Privilege::ReadFiles,
+        Privilege::ReadVolume,
+        Privilege::Refresh,
+        Privilege::Select,
+        Privilege::SetSharePermission,
+        Privilege::UseCatalog,
+        Privilege::UseConnection,
+        Privilege::UseMarketplaceAssets,
+        Privilege::UseProvider,
+        Privilege::UseRecipient,
+        Privilege::UseSchema,
+        Privilege::UseShare,
+        Privilege::WriteFiles,
+        Privilege::WriteVolume,
+    ];
+
+    /// Returns the name used by the Unity Catalog API i.e. 'USE_CATALOG'.
+    pub fn as_str(&self) -> &'static str {
         match self {
-            SecurableType::Catalog => "catalog".to_string(),
-            SecurableType::Schema => "schema".to_string(),
-            SecurableType::Table => "table".to_string(),
-            SecurableType::StorageCredential => "storage_credential".to_string(),
-            SecurableType::ExternalLocation => "external_location".to_string(),
-            SecurableType::Function => "function".to_string(),
-            SecurableType::Share => "share".to_string(),
-            SecurableType::Provider => "provider".to_string(),
-            SecurableType::Recipient => "recipient".to_string(),
-            SecurableType::Metastore => "metastore".to_string(),
-            SecurableType::Volume => "volume".to_string(),
-            SecurableType::Connection => "connection".to_string(),
+            Privilege::Access => "ACCESS",
+            Privilege::AllPrivileges => "ALL_PRIVILEGES",
+            Privilege::ApplyTag => "APPLY_TAG",
+            Privilege::Browse => "BROWSE",
+            Privilege::CreateCatalog => "CREATE_CATALOG",
+            Privilege::CreateCleanRoom => "CREATE_CLEAN_ROOM",
+            Privilege::CreateConnection => "CREATE_CONNECTION",
+            Privilege::CreateExternalLocation => "CREATE_EXTERNAL_LOCATION",
+            Privilege::CreateExternalTable => "CREATE_EXTERNAL_TABLE",
+            Privilege::CreateExternalVolume => "CREATE_EXTERNAL_VOLUME",
+            Privilege::CreateForeignCatalog => "CREATE_FOREIGN_CATALOG",
+            Privilege::CreateForeignSecurable => "CREATE_FOREIGN_SECURABLE",
+            Privilege::CreateFunction => "CREATE_FUNCTION",
+            Privilege::CreateManagedStorage => "CREATE_MANAGED_STORAGE",
+            Privilege::CreateMaterializedView => "CREATE_MATERIALIZED_VIEW",
+            Privilege::CreateModel => "CREATE_MODEL",
+            Privilege::CreateProvider => "CREATE_PROVIDER",
+            Privilege::CreateRecipient => "CREATE_RECIPIENT",
+            Privilege::CreateSchema => "CREATE_SCHEMA",
+            Privilege::CreateServiceCredential => "CREATE_SERVICE_CREDENTIAL",
+            Privilege::CreateShare => "CREATE_SHARE",
+            Privilege::CreateStorageCredential => "CREATE_STORAGE_CREDENTIAL",
+            Privilege::CreateTable => "CREATE_TABLE",
+            Privilege::CreateVolume => "CREATE_VOLUME",
+            Privilege::Execute => "EXECUTE",
+            Privilege::ExecuteCleanRoomTask => "EXECUTE_CLEAN_ROOM_TASK",
+            Privilege::ExternalUseSchema => "EXTERNAL_USE_SCHEMA",
+            Privilege::Manage => "MANAGE",
+            Privilege::ManageAllowlist => "MANAGE_ALLOWLIST",
+            Privilege::Modify => "MODIFY",
+            Privilege::ModifyCleanRoom => "MODIFY_CLEAN_ROOM",
+            Privilege::ReadFiles => "READ_FILES",
+            Privilege::ReadVolume => "READ_VOLUME",
+            Privilege::Refresh => "REFRESH",
+            Privilege::Select => "SELECT",
+            Privilege::SetSharePermission => "SET_SHARE_PERMISSION",
+            Privilege::UseCatalog => "USE_CATALOG",
+            Privilege::UseConnection => "USE_CONNECTION",
+            Privilege::UseMarketplaceAssets => "USE_MARKETPLACE_ASSETS",
+            Privilege::UseProvider => "USE_PROVIDER",
+            Privilege::UseRecipient => "USE_RECIPIENT",
+            Privilege::UseSchema => "USE_SCHEMA",
+            Privilege::UseShare => "USE_SHARE",
+            Privilege::WriteFiles => "WRITE_FILES",
+            Privilege::WriteVolume => "WRITE_VOLUME",
+        }
+    }
+
+    /// Returns true if a grant of `granted` confers this privilege, either because it is the
+    /// same privilege or because it is `ALL_PRIVILEGES`. Unknown privilege names never match.
+    pub fn is_granted_by(&self, granted: &str) -> bool {
+        match granted.parse::<Privilege>() {
+            Ok(granted) => granted == *self || granted == Privilege::AllPrivileges,
+            Err(_) => false,
         }
     }
 }
+
+impl std::str::FromStr for Privilege {
+    type Err = ();
+    /// Converts a privilege name to a `Privilege` enum, ignoring case.
+    /// Accepts both the API form 'USE_CATALOG' and the SQL form 'USE CATALOG'.
+    /// Returns an error if the input string does not match any privilege
+    fn from_str(input: &str) -> Result<Privilege, Self::Err> {
+        let normalized: String = input.trim().replace(' ', "_");
+        Privilege::ALL
+            .into_iter()
+            .find(|privilege| privilege.as_str().eq_ignore_ascii_case(&normalized))
+            .ok_or(())
+    }
+}
+
+impl std::fmt::Display for Privilege {
+    /// Converts a `Privilege` enum to the name used by the Unity Catalog API
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,610 @@
+use super::api_client::APIClient;
+use super::metastore::{Catalog, Client, ListOptions, Schema, Table};
+use super::permissions::{self, EffectivePrivileges, SecurableType};
+use crate::error::{ApiError, Error};
+use futures::stream::{self, StreamExt};
+use polars::prelude::{DataFrame, NamedFrom, ParquetWriter, Series};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Value of the `privilege` column for the owner of an object.
+pub const OWNER: &str = "OWNER";
+
+/// One line of the grants report: a principal holding a privilege on an object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrantRow {
+    /// Type of the object.
+    pub securable_type: SecurableType,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// The user, service principal or group holding the privilege.
+    pub principal: String,
+    /// The privilege i.e. 'SELECT', or `OWNER` for the owner of the object.
+    pub privilege: String,
+    /// The parent object the privilege is inherited from, `None` when granted on the object.
+    pub inherited_from: Option<String>,
+}
+
+/// Output format of a grants report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Comma separated values with a header line.
+    Csv,
+    /// Apache Parquet, one column per field.
+    Parquet,
+    /// A JSON array of rows.
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<ReportFormat, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "csv" => Ok(ReportFormat::Csv),
+            "parquet" => Ok(ReportFormat::Parquet),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ReportFormat {
+    /// Returns the format matching the extension of a path i.e. `grants.csv`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<ReportFormat> {
+        path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+    }
+}
+
+/// An object left out of the report because it could not be read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedObject {
+    /// Type of the object.
+    pub securable_type: SecurableType,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// Why the object could not be read.
+    pub reason: String,
+}
+
+/// Who has access to what: the owner and every effective privilege of each object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GrantsReport {
+    /// Rows ordered by object, each catalog followed by its schemas and their tables.
+    pub rows: Vec<GrantRow>,
+    /// Catalogs and schemas whose children could not be listed, in the same order. Neither
+    /// they nor their children are in `rows`.
+    #[serde(default)]
+    pub skipped: Vec<SkippedObject>,
+}
+
+impl GrantsReport {
+    /// Writes the report to a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Report` if the file cannot be written and `Error::Polars` if the
+    /// Parquet file cannot be encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// report.write("grants.parquet", ReportFormat::Parquet)?;
+    /// ```
+    pub fn write(&self, path: impl AsRef<Path>, format: ReportFormat) -> Result<(), Error> {
+        let path: &Path = path.as_ref();
+        let file: File = File::create(path)
+            .map_err(|e| Error::Report(format!("unable to write {}: {}", path.display(), e)))?;
+        let mut writer: BufWriter<File> = BufWriter::new(file);
+        match format {
+            ReportFormat::Csv => self.write_csv(&mut writer)?,
+            ReportFormat::Parquet => self.write_parquet(&mut writer)?,
+            ReportFormat::Json => self.write_json(&mut writer)?,
+        }
+        writer
+            .flush()
+            .map_err(|e| Error::Report(format!("unable to write {}: {}", path.display(), e)))
+    }
+
+    /// Writes the report as CSV with a header line.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Report` if writing fails.
+    pub fn write_csv(&self, mut writer: impl Write) -> Result<(), Error> {
+        let mut csv: String =
+            String::from("securable_type,full_name,principal,privilege,inherited_from\n");
+        for row in &self.rows {
+            let fields: [String; 5] = [
+                type_name(&row.securable_type),
+                csv_field(&row.full_name),
+                csv_field(&row.principal),
+                csv_field(&row.privilege),
+                csv_field(row.inherited_from.as_deref().unwrap_or_default()),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        writer
+            .write_all(csv.as_bytes())
+            .map_err(|e| Error::Report(format!("unable to write the report: {}", e)))
+    }
+
+    /// Writes the report as a JSON array.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Json` if writing fails.
+    pub fn write_json(&self, writer: impl Write) -> Result<(), Error> {
+        Ok(serde_json::to_writer_pretty(writer, &self.rows)?)
+    }
+
+    /// Writes the report as Parquet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Polars` if the dataframe cannot be built or written.
+    pub fn write_parquet(&self, writer: impl Write) -> Result<(), Error> {
+        let mut df: DataFrame = self.to_dataframe()?;
+        ParquetWriter::new(writer).finish(&mut df)?;
+        Ok(())
+    }
+
+    /// Returns the report as a polars dataframe.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Polars` if the dataframe cannot be built.
+    pub fn to_dataframe(&self) -> Result<DataFrame, Error> {
+        let column = |name: &str, values: Vec<&str>| Series::new(name, values);
+        let df: DataFrame = DataFrame::new(vec![
+            Series::new(
+                "securable_type",
+                self.rows
+                    .iter()
+                    .map(|row| type_name(&row.securable_type))
+                    .collect::<Vec<String>>(),
+            ),
+            column(
+                "full_name",
+                self.rows.iter().map(|row| row.full_name.as_str()).collect(),
+            ),
+            column(
+                "principal",
+                self.rows.iter().map(|row| row.principal.as_str()).collect(),
+            ),
+            column(
+                "privilege",
+                self.rows.iter().map(|row| row.privilege.as_str()).collect(),
+            ),
+            Series::new(
+                "inherited_from",
+                self.rows
+                    .iter()
+                    .map(|row| row.inherited_from.as_deref())
+                    .collect::<Vec<Option<&str>>>(),
+            ),
+        ])?;
+        Ok(df)
+    }
+}
+
+/// Crawls every catalog, schema and table the caller can see and reports who has access to them.
+///
+/// The owner and the effective privileges of each object are fetched with at most
+/// `concurrency` requests in flight. With a checkpoint file, every listing and every crawled
+/// object is appended to it as soon as it is done, and a crawl that failed part way resumes
+/// from the checkpoint instead of requesting them again. Delete the checkpoint to start over.
+///
+/// A catalog or schema whose children cannot be listed, i.e. because the caller lacks access,
+/// is left out and reported in `GrantsReport::skipped` instead of failing the crawl.
+///
+/// # Examples
+///
+/// ```ignore
+/// let report: GrantsReport = GrantsCrawler::new(api_client)
+///     .concurrency(16)
+///     .checkpoint("grants.checkpoint")
+///     .crawl()
+///     .await?;
+/// report.write("grants.csv", ReportFormat::Csv)?;
+/// ```
+pub struct GrantsCrawler {
+    api_client: APIClient,
+    concurrency: usize,
+    checkpoint: Option<PathBuf>,
+}
+
+// an object found while listing the metastore
+#[derive(Clone, Serialize, Deserialize)]
+struct CrawlObject {
+    securable_type: SecurableType,
+    full_name: String,
+    owner: String,
+}
+
+// one line of the checkpoint file
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum CheckpointEntry {
+    // the children of an object, or the catalogs when there is no parent
+    Listed {
+        parent: Option<String>,
+        children: Vec<CrawlObject>,
+    },
+    // a catalog or schema whose children could not be listed
+    Skipped(SkippedObject),
+    // the rows of a crawled object
+    Crawled {
+        securable_type: SecurableType,
+        full_name: String,
+        rows: Vec<GrantRow>,
+    },
+}
+
+type ObjectKey = (SecurableType, String);
+
+// what the crawl learned so far, appended to the checkpoint file as it goes
+#[derive(Default)]
+struct Checkpoint {
+    file: Option<(File, PathBuf)>,
+    listed: HashMap<Option<String>, Vec<CrawlObject>>,
+    skipped: HashMap<String, SkippedObject>,
+    crawled: HashMap<ObjectKey, Vec<GrantRow>>,
+}
+
+impl Checkpoint {
+    fn record(&mut self, entry: CheckpointEntry) -> Result<(), Error> {
+        if let Some((file, path)) = self.file.as_mut() {
+            let line: String = serde_json::to_string(&entry)? + "\n";
+            file.write_all(line.as_bytes())
+                .map_err(|e| checkpoint_error(path, e))?;
+        }
+        self.insert(entry);
+        Ok(())
+    }
+
+    fn insert(&mut self, entry: CheckpointEntry) {
+        match entry {
+            CheckpointEntry::Listed { parent, children } => {
+                self.listed.insert(parent, children);
+            }
+            CheckpointEntry::Skipped(skipped) => {
+                self.skipped.insert(skipped.full_name.clone(), skipped);
+            }
+            CheckpointEntry::Crawled {
+                securable_type,
+                full_name,
+                rows,
+            } => {
+                self.crawled.insert((securable_type, full_name), rows);
+            }
+        }
+    }
+
+    // the children of `parent`, empty for a skipped or unlisted parent
+    fn children(&self, parent: &CrawlObject) -> &[CrawlObject] {
+        self.listed
+            .get(&Some(parent.full_name.clone()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn is_listed(&self, parent: &CrawlObject) -> bool {
+        self.listed.contains_key(&Some(parent.full_name.clone()))
+            || self.skipped.contains_key(&parent.full_name)
+    }
+}
+
+impl GrantsCrawler {
+    /// How many requests are in flight unless configured otherwise.
+    pub const DEFAULT_CONCURRENCY: usize = 8;
+
+    /// Creates a crawler without a checkpoint.
+    pub fn new(api_client: APIClient) -> Self {
+        GrantsCrawler {
+            api_client,
+            concurrency: GrantsCrawler::DEFAULT_CONCURRENCY,
+            checkpoint: None,
+        }
+    }
+
+    /// Sets the maximum number of requests in flight, at least 1.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Records crawled objects in a file and resumes from it, see `GrantsCrawler`.
+    pub fn checkpoint(mut self, path: impl AsRef<Path>) -> Self {
+        self.checkpoint = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Lists every object and fetches the owner and effective privileges of those not in
+    /// the checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if listing fails, if fetching the privileges of an object fails and
+    /// `Error::Report` if the checkpoint cannot be read or written. Listings and objects
+    /// crawled before the failure are kept in the checkpoint. A catalog or schema whose children
+    /// cannot be listed is skipped instead, see `GrantsReport::skipped`.
+    pub async fn crawl(&self) -> Result<GrantsReport, Error> {
+        let mut checkpoint: Checkpoint = self.open_checkpoint()?;
+        let (objects, skipped) = self.list_objects(&mut checkpoint).await?;
+        let pending: Vec<&CrawlObject> = objects
+            .iter()
+            .filter(|object| !checkpoint.crawled.contains_key(&key(object)))
+            .collect();
+        log::info!(
+            "Crawling grants of {} objects, {} already crawled.",
+            pending.len(),
+            objects.len() - pending.len()
+        );
+
+        let mut fetched = std::pin::pin!(stream::iter(pending)
+            .map(|object| async move {
+                let rows: Vec<GrantRow> = self.fetch_rows(object).await?;
+                Ok::<(ObjectKey, Vec<GrantRow>), Error>((key(object), rows))
+            })
+            .buffer_unordered(self.concurrency));
+        while let Some(result) = fetched.next().await {
+            let ((securable_type, full_name), rows) = result?;
+            checkpoint.record(CheckpointEntry::Crawled {
+                securable_type,
+                full_name,
+                rows,
+            })?;
+        }
+
+        // objects of an older crawl that no longer exist are left out
+        let rows: Vec<GrantRow> = objects
+            .iter()
+            .flat_map(|object| checkpoint.crawled.remove(&key(object)).unwrap_or_default())
+            .collect();
+        Ok(GrantsReport { rows, skipped })
+    }
+
+    /// Lists the catalogs, their schemas and their tables, each catalog followed by its
+    /// schemas and each schema followed by its tables, along with the catalogs and schemas
+    /// whose children could not be listed. Listings found in the checkpoint are not requested again.
+    async fn list_objects(
+        &self,
+        checkpoint: &mut Checkpoint,
+    ) -> Result<(Vec<CrawlObject>, Vec<SkippedObject>), Error> {
+        let client: &Client = &Client::from_api_client(self.api_client.clone());
+        let options: &ListOptions = &ListOptions::default();
+
+        if !checkpoint.listed.contains_key(&None) {
+            let catalogs: Vec<Catalog> = client.list_catalogs(options).await?;
+            checkpoint.record(CheckpointEntry::Listed {
+                parent: None,
+                children: catalogs
+                    .into_iter()
+                    .map(|catalog| CrawlObject {
+                        securable_type: SecurableType::Catalog,
+                        full_name: catalog.name,
+                        owner: catalog.owner,
+                    })
+                    .collect(),
+            })?;
+        }
+        let catalogs: Vec<CrawlObject> = checkpoint.listed[&None].clone();
+
+        let unlisted: Vec<&CrawlObject> = catalogs
+            .iter()
+            .filter(|catalog| !checkpoint.is_listed(catalog))
+            .collect();
+        let mut listings = std::pin::pin!(stream::iter(unlisted)
+            .map(|catalog| async move {
+                (
+                    catalog,
+                    client.list_schemas(&catalog.full_name, options).await,
+                )
+            })
+            .buffered(self.concurrency));
+        while let Some((catalog, result)) = listings.next().await {
+            let schemas: Result<Vec<CrawlObject>, Error> = result.map(|schemas: Vec<Schema>| {
+                schemas
+                    .into_iter()
+                    .map(|schema| CrawlObject {
+                        securable_type: SecurableType::Schema,
+                        full_name: schema.full_name,
+                        owner: schema.owner,
+                    })
+                    .collect()
+            });
+            checkpoint.record(listing(catalog, schemas)?)?;
+        }
+        let schemas: Vec<CrawlObject> = catalogs
+            .iter()
+            .flat_map(|catalog| checkpoint.children(catalog).to_vec())
+            .collect();
+
+        let unlisted: Vec<&CrawlObject> = schemas
+            .iter()
+            .filter(|schema| !checkpoint.is_listed(schema))
+            .collect();
+        let mut listings = std::pin::pin!(stream::iter(unlisted)
+            .map(|schema| async move {
+                let (catalog_name, schema_name) =
+                    schema.full_name.split_once('.').unwrap_or_default();
+                (
+                    schema,
+                    client.list_tables(catalog_name, schema_name, options).await,
+                )
+            })
+            .buffered(self.concurrency));
+        while let Some((schema, result)) = listings.next().await {
+            let tables: Result<Vec<CrawlObject>, Error> = result.map(|tables: Vec<Table>| {
+                tables
+                    .into_iter()
+                    .map(|table| CrawlObject {
+                        securable_type: SecurableType::Table,
+                        full_name: table.full_name,
+                        owner: table.owner,
+                    })
+                    .collect()
+            });
+            checkpoint.record(listing(schema, tables)?)?;
+        }
+
+        let mut objects: Vec<CrawlObject> = Vec::new();
+        let mut skipped: Vec<SkippedObject> = Vec::new();
+        for catalog in &catalogs {
+            if let Some(unreadable) = checkpoint.skipped.get(&catalog.full_name) {
+                skipped.push(unreadable.clone());
+                continue;
+            }
+            objects.push(catalog.clone());
+            for schema in checkpoint.children(catalog) {
+                if let Some(unreadable) = checkpoint.skipped.get(&schema.full_name) {
+                    skipped.push(unreadable.clone());
+                    continue;
+                }
+                objects.push(schema.clone());
+                objects.extend_from_slice(checkpoint.children(schema));
+            }
+        }
+        Ok((objects, skipped))
+    }
+
+    /// Returns the owner row and one row per effective privilege of an object.
+    async fn fetch_rows(&self, object: &CrawlObject) -> Result<Vec<GrantRow>, Error> {
+        let privileges: EffectivePrivileges = permissions::get_effective_permissions(
+            self.api_client.clone(),
+            object.securable_type.clone(),
+            &object.full_name,
+            None,
+        )
+        .await?;
+
+        let row = |principal: &str, privilege: &str, inherited_from: Option<String>| GrantRow {
+            securable_type: object.securable_type.clone(),
+            full_name: object.full_name.clone(),
+            principal: principal.to_string(),
+            privilege: privilege.to_string(),
+            inherited_from,
+        };
+        let mut rows: Vec<GrantRow> = Vec::new();
+        if !object.owner.is_empty() {
+            rows.push(row(&object.owner, OWNER, None));
+        }
+        for assignment in &privileges.assignments {
+            for privilege in &assignment.privileges {
+                rows.push(row(
+                    &assignment.principal,
+                    &privilege.privilege,
+                    privilege.inherited_from_name.clone(),
+                ));
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Reads what the checkpoint recorded so far and opens it for appending, ending a line cut
+    /// short by a crash first. A line cut short is skipped.
+    fn open_checkpoint(&self) -> Result<Checkpoint, Error> {
+        let mut checkpoint: Checkpoint = Checkpoint::default();
+        let Some(path) = &self.checkpoint else {
+            return Ok(checkpoint);
+        };
+        let mut file: File = OpenOptions::new()
+            .read(true)
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| checkpoint_error(path, e))?;
+        for line in BufReader::new(&file).lines() {
+            let line: String = line.map_err(|e| checkpoint_error(path, e))?;
+            match serde_json::from_str::<CheckpointEntry>(&line) {
+                Ok(entry) => checkpoint.insert(entry),
+                Err(e) => log::warn!("Skipping checkpoint line of {}: {}", path.display(), e),
+            }
+        }
+
+        let mut last: [u8; 1] = [b'\n'];
+        if file
+            .seek(SeekFrom::End(0))
+            .map_err(|e| checkpoint_error(path, e))?
+            > 0
+        {
+            file.seek(SeekFrom::End(-1))
+                .and_then(|_| file.read_exact(&mut last))
+                .map_err(|e| checkpoint_error(path, e))?;
+        }
+        if last[0] != b'\n' {
+            file.write_all(b"\n")
+                .map_err(|e| checkpoint_error(path, e))?;
+        }
+        checkpoint.file = Some((file, path.clone()));
+        Ok(checkpoint)
+    }
+}
+
+fn key(object: &CrawlObject) -> ObjectKey {
+    (object.securable_type.clone(), object.full_name.clone())
+}
+
+/// The checkpoint entry for the children of `parent`, or a skipped entry if the caller may not
+/// list them.
+fn listing(
+    parent: &CrawlObject,
+    children: Result<Vec<CrawlObject>, Error>,
+) -> Result<CheckpointEntry, Error> {
+    match children {
+        Ok(children) => Ok(CheckpointEntry::Listed {
+            parent: Some(parent.full_name.clone()),
+            children,
+        }),
+        Err(Error::Api(e)) if is_unreadable(&e) => {
+            log::warn!(
+                "Skipping {} {}: {}",
+                parent.securable_type,
+                parent.full_name,
+                e
+            );
+            Ok(CheckpointEntry::Skipped(SkippedObject {
+                securable_type: parent.securable_type.clone(),
+                full_name: parent.full_name.clone(),
+                reason: e.to_string(),
+            }))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// client errors such as PERMISSION_DENIED won't go away when the crawl is resumed
+fn is_unreadable(e: &ApiError) -> bool {
+    (400..500).contains(&e.status) && e.status != 429
+}
+
+fn checkpoint_error(path: &Path, e: std::io::Error) -> Error {
+    Error::Report(format!(
+        "unable to use checkpoint {}: {}",
+        path.display(),
+        e
+    ))
+}
+
+/// Name of a securable type as serialized, i.e. 'EXTERNAL_LOCATION'.
+fn type_name(securable_type: &SecurableType) -> String {
+    securable_type.to_string().to_uppercase()
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,162 @@
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::Method;
+use std::time::{Duration, SystemTime};
+
+/// Retry policy applied by the `APIClient` to every request.
+///
+/// Requests are retried when the response status is in `retryable_statuses` or when the
+/// request fails with a timeout/connection error. The delay between attempts grows
+/// exponentially from `base_delay` and is capped at `max_delay`. When the server sends a
+/// `Retry-After` header its value is used instead of the computed backoff.
+///
+/// Only idempotent requests (GET, HEAD, OPTIONS and DELETE) are retried unless `retry_writes`
+/// is set, since a POST, PATCH or PUT that timed out or failed with a 5xx may already have been
+/// applied and retrying it could create or grant twice.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts including the first request. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each following retry.
+    pub base_delay: Duration,
+    /// Upper bound on any single delay, including `Retry-After` values.
+    pub max_delay: Duration,
+    /// Random jitter applied to the computed backoff as a fraction of the delay, clamped to
+    /// 0.0 - 1.0.
+    pub jitter: f64,
+    /// HTTP status codes that are considered transient.
+    pub retryable_statuses: Vec<u16>,
+    /// Also retry POST, PATCH and PUT requests. Only enable it for endpoints that are safe to
+    /// call twice.
+    pub retry_writes: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.2,
+            retryable_statuses: vec![429, 500, 502, 503, 504],
+            retry_writes: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let api_client: APIClient = APIClient::new(workspace_name, db_token).with_retry_policy(RetryPolicy::none());
+    /// ```
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Sets the total number of attempts including the first request.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Sets the upper bound on any single delay.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets the jitter as a fraction of the computed delay, clamped to 0.0 - 1.0.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Replaces the set of HTTP status codes that are retried.
+    pub fn with_retryable_statuses(mut self, retryable_statuses: Vec<u16>) -> Self {
+        self.retryable_statuses = retryable_statuses;
+        self
+    }
+
+    /// Sets whether POST, PATCH and PUT requests are retried.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let api_client: APIClient = api_client.with_retry_policy(RetryPolicy::default().with_retry_writes(true));
+    /// ```
+    pub fn with_retry_writes(mut self, retry_writes: bool) -> Self {
+        self.retry_writes = retry_writes;
+        self
+    }
+
+    /// Returns true if requests with the given method may be retried.
+    pub fn is_retryable_method(&self, method: &Method) -> bool {
+        self.retry_writes
+            || [Method::GET, Method::HEAD, Method::OPTIONS, Method::DELETE].contains(method)
+    }
+
+    /// Returns true if a response with the given status should be retried.
+    pub fn is_retryable_status(&self, status: u16) -> bool {
+        self.retryable_statuses.contains(&status)
+    }
+
+    /// Returns true if a transport error should be retried.
+    pub fn is_retryable_error(&self, error: &reqwest::Error) -> bool {
+        error.is_timeout() || error.is_connect()
+    }
+
+    /// Computes the delay before the next attempt.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The attempt that just failed, starting at 1.
+    /// * `headers` - Headers of the failed response, if any, to honor `Retry-After`.
+    pub fn delay(&self, attempt: u32, headers: Option<&HeaderMap>) -> Duration {
+        if let Some(retry_after) = headers.and_then(parse_retry_after) {
+            return retry_after.min(self.max_delay);
+        }
+
+        let exponent: u32 = attempt.saturating_sub(1).min(16);
+        let backoff: Duration = self
+            .base_delay
+            .saturating_mul(2u32.pow(exponent))
+            .min(self.max_delay);
+
+        // the field is public, so a policy built without `with_jitter` may be out of range
+        let jitter: f64 = if self.jitter.is_nan() {
+            0.0
+        } else {
+            self.jitter.clamp(0.0, 1.0)
+        };
+        if jitter == 0.0 || backoff.is_zero() {
+            return backoff;
+        }
+        let factor: f64 = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        backoff.mul_f64(factor).min(self.max_delay)
+    }
+}
+
+/// Parses a `Retry-After` header given either as delay-seconds or as an HTTP date.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value: &str = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date: SystemTime = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
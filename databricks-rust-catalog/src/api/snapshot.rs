@@ -0,0 +1,505 @@
+use super::api_client::APIClient;
+use super::evaluator::{
+    AccessExplanation, Decision, Operation, PrivilegeEvaluator, SecurableFacts,
+};
+use super::grants::Plan;
+use super::metastore::{Catalog, Client, ListOptions, Schema, Table};
+use super::permissions::{
+    self, EffectivePrivilege, EffectivePrivilegeAssignment, EffectivePrivileges, Identity,
+    Privilege, PrivilegeAssignment, PrivilegeAssignmentsResponse, SecurableType,
+    ACCOUNT_USERS_GROUP,
+};
+use crate::error::Error;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::Path;
+
+/// Number of objects whose grants are fetched at the same time while taking a snapshot.
+const SNAPSHOT_CONCURRENCY: usize = 8;
+
+/// Ownership and direct grants of a catalog tree and the group memberships of some principals,
+/// to evaluate access offline.
+///
+/// Decisions follow the same rules as `permissions::evaluate_access`: grants on a catalog or
+/// schema are inherited by the objects inside it and the `PrivilegeEvaluator` makes the decision.
+/// The snapshot can be changed to answer what-if questions, i.e. `add_member` followed by
+/// `can_read`, without touching the workspace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GrantsSnapshot {
+    /// The catalog, its schemas and their tables.
+    pub objects: Vec<SnapshotObject>,
+    /// The groups each user or group directly belongs to.
+    #[serde(default)]
+    pub memberships: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Ownership and direct grants of one object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotObject {
+    /// Type of the object.
+    pub securable_type: SecurableType,
+    /// Fully qualified name of the object.
+    pub full_name: String,
+    /// The user or group owning the object.
+    pub owner: Option<String>,
+    /// Privileges granted directly on the object, per principal.
+    #[serde(default)]
+    pub privilege_assignments: Vec<PrivilegeAssignment>,
+}
+
+/// Snapshots the ownership and direct grants of a catalog, its schemas and their tables,
+/// together with the group memberships of the given principals.
+///
+/// # Arguments
+///
+/// * `api_client` - API client object for making HTTP requests.
+/// * `catalog_name` - The catalog to snapshot.
+/// * `principals` - Users whose group memberships are included, i.e. everyone the snapshot
+///   will be asked about. Group grants are not considered for other principals.
+///
+/// # Returns
+///
+/// * `GrantsSnapshot` - The snapshot, objects ordered from the catalog down.
+///
+/// # Errors
+///
+/// Returns an `Error` if any API request fails or if a response cannot be parsed.
+///
+/// # Examples
+///
+/// ```ignore
+/// let snapshot: GrantsSnapshot = take_snapshot(api_client, "main", &["first.last@example.com"]).await?;
+/// snapshot.save("main-grants.json")?;
+/// ```
+pub async fn take_snapshot(
+    api_client: APIClient,
+    catalog_name: &str,
+    principals: &[&str],
+) -> Result<GrantsSnapshot, Error> {
+    let client: Client = Client::from_api_client(api_client.clone());
+    let options: ListOptions = ListOptions::default();
+
+    let catalog: Catalog = client.get_catalog(catalog_name.to_string()).await?;
+    let mut owners: Vec<(SecurableType, String, String)> =
+        vec![(SecurableType::Catalog, catalog.name, catalog.owner)];
+    let schemas: Vec<Schema> = client.list_schemas(catalog_name, &options).await?;
+    for schema in schemas {
+        let tables: Vec<Table> = client
+            .list_tables(catalog_name, &schema.name, &options)
+            .await?;
+        owners.push((SecurableType::Schema, schema.full_name, schema.owner));
+        owners.extend(
+            tables
+                .into_iter()
+                .map(|table| (SecurableType::Table, table.full_name, table.owner)),
+        );
+    }
+    log::info!(
+        "Snapshotting grants of {} objects in {}",
+        owners.len(),
+        catalog_name
+    );
+
+    // buffered keeps the objects in order
+    let objects: Vec<SnapshotObject> = stream::iter(owners)
+        .map(|(securable_type, full_name, owner)| {
+            let api_client: APIClient = api_client.clone();
+            async move {
+                let grants: PrivilegeAssignmentsResponse =
+                    permissions::get_permissions(api_client, securable_type.clone(), &full_name)
+                        .await?;
+                Ok::<SnapshotObject, Error>(SnapshotObject {
+                    securable_type,
+                    full_name,
+                    owner: Some(owner),
+                    privilege_assignments: grants.privilege_assignments.unwrap_or_default(),
+                })
+            }
+        })
+        .buffered(SNAPSHOT_CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    let mut memberships: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for principal in principals {
+        for (member, groups) in
+            permissions::group_memberships(api_client.clone(), principal).await?
+        {
+            memberships.entry(member).or_default().extend(groups);
+        }
+    }
+
+    Ok(GrantsSnapshot {
+        objects,
+        memberships,
+    })
+}
+
+impl GrantsSnapshot {
+    /// Reads a snapshot saved with `save`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the file cannot be read or is not a snapshot.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+        let contents: String = std::fs::read_to_string(path)
+            .map_err(|e| Error::Snapshot(format!("unable to read {}: {}", path.display(), e)))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| Error::Snapshot(format!("unable to parse {}: {}", path.display(), e)))
+    }
+
+    /// Writes the snapshot as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path: &Path = path.as_ref();
+        let contents: String = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, contents)
+            .map_err(|e| Error::Snapshot(format!("unable to write {}: {}", path.display(), e)))
+    }
+
+    /// Returns an object of the snapshot. Names are case insensitive.
+    pub fn object(
+        &self,
+        securable_type: &SecurableType,
+        full_name: &str,
+    ) -> Option<&SnapshotObject> {
+        self.objects.iter().find(|object| {
+            &object.securable_type == securable_type
+                && object.full_name.eq_ignore_ascii_case(full_name)
+        })
+    }
+
+    /// Resolves a principal and every group it belongs to from the snapshotted memberships,
+    /// like `permissions::resolve_identity` does online.
+    pub fn identity(&self, principal: &str) -> Identity {
+        // memberships are keyed by the SCIM user name, which may differ in case
+        let user_name: &str = self
+            .memberships
+            .keys()
+            .find(|member| member.eq_ignore_ascii_case(principal))
+            .map_or(principal, String::as_str);
+        let mut groups: Vec<String> = Vec::new();
+        let mut pending: VecDeque<&str> = VecDeque::from([user_name]);
+        while let Some(member) = pending.pop_front() {
+            for group in self.memberships.get(member).into_iter().flatten() {
+                if !groups.contains(group) {
+                    groups.push(group.clone());
+                    pending.push_back(group);
+                }
+            }
+        }
+        if !groups.iter().any(|g| g == ACCOUNT_USERS_GROUP) {
+            groups.push(ACCOUNT_USERS_GROUP.to_string());
+        }
+        Identity {
+            user_name: user_name.to_string(),
+            groups,
+        }
+    }
+
+    /// Evaluates an operation of a principal on an object of the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the object or one of its parents is not in the snapshot.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let decision: Decision = snapshot.evaluate("first.last@example.com", SecurableType::Table, "main.sales.orders", Operation::Read)?;
+    /// ```
+    pub fn evaluate(
+        &self,
+        principal: &str,
+        securable_type: SecurableType,
+        full_name: &str,
+        operation: Operation,
+    ) -> Result<Decision, Error> {
+        let identity: Identity = self.identity(principal);
+        let levels: Vec<SecurableFacts> = self.levels(securable_type, full_name)?;
+        Ok(PrivilegeEvaluator::new(&identity, &levels).evaluate(operation))
+    }
+
+    /// Explains why a principal holds a privilege on an object of the snapshot or not,
+    /// like `permissions::explain_access` does online.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the object or one of its parents is not in the snapshot.
+    pub fn explain(
+        &self,
+        principal: &str,
+        privilege: Privilege,
+        securable_type: SecurableType,
+        full_name: &str,
+    ) -> Result<AccessExplanation, Error> {
+        let identity: Identity = self.identity(principal);
+        let levels: Vec<SecurableFacts> = self.levels(securable_type, full_name)?;
+        Ok(PrivilegeEvaluator::new(&identity, &levels).explain(privilege))
+    }
+
+    /// Checks if a principal holds a privilege on an object of the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the object or one of its parents is not in the snapshot.
+    pub fn check(
+        &self,
+        principal: &str,
+        privilege: Privilege,
+        securable_type: SecurableType,
+        full_name: &str,
+    ) -> Result<bool, Error> {
+        let decision: Decision = self.evaluate(
+            principal,
+            securable_type,
+            full_name,
+            Operation::Privilege(privilege),
+        )?;
+        Ok(decision.allowed)
+    }
+
+    /// Checks if a principal can read a table of the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the table or one of its parents is not in the snapshot.
+    pub fn can_read(&self, full_name: &str, principal: &str) -> Result<bool, Error> {
+        let decision: Decision =
+            self.evaluate(principal, SecurableType::Table, full_name, Operation::Read)?;
+        Ok(decision.allowed)
+    }
+
+    /// Checks if a principal can write to a table of the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the table or one of its parents is not in the snapshot.
+    pub fn can_write(&self, full_name: &str, principal: &str) -> Result<bool, Error> {
+        let decision: Decision =
+            self.evaluate(principal, SecurableType::Table, full_name, Operation::Write)?;
+        Ok(decision.allowed)
+    }
+
+    /// What if `member`, a user or group, is added to `group`.
+    pub fn add_member(&mut self, member: &str, group: &str) {
+        self.memberships
+            .entry(member.to_string())
+            .or_default()
+            .insert(group.to_string());
+    }
+
+    /// What if `member` is removed from `group`. Membership through nested groups is kept.
+    pub fn remove_member(&mut self, member: &str, group: &str) {
+        if let Some(groups) = self.memberships.get_mut(member) {
+            groups.remove(group);
+        }
+    }
+
+    /// What if privileges are granted on an object. Principals are matched ignoring case, so
+    /// privileges are added to an existing assignment spelled differently.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the object is not in the snapshot.
+    pub fn grant(
+        &mut self,
+        securable_type: SecurableType,
+        full_name: &str,
+        principal: &str,
+        privileges: &[Privilege],
+    ) -> Result<(), Error> {
+        let object: &mut SnapshotObject = self.object_mut(&securable_type, full_name)?;
+        let position: Option<usize> = object
+            .privilege_assignments
+            .iter()
+            .position(|assignment| is_principal(assignment, principal));
+        let assignment: &mut PrivilegeAssignment = match position {
+            Some(position) => &mut object.privilege_assignments[position],
+            None => {
+                object.privilege_assignments.push(PrivilegeAssignment {
+                    object_name: object.full_name.clone(),
+                    object_type: Some(securable_type),
+                    principal: Some(principal.to_string()),
+                    privileges: Some(Vec::new()),
+                });
+                object.privilege_assignments.last_mut().unwrap()
+            }
+        };
+        let held: &mut Vec<String> = assignment.privileges.get_or_insert_with(Vec::new);
+        for privilege in privileges {
+            if !held
+                .iter()
+                .any(|p| p.eq_ignore_ascii_case(privilege.as_str()))
+            {
+                held.push(privilege.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// What if privileges are revoked on an object. Principals are matched ignoring case.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the object is not in the snapshot.
+    pub fn revoke(
+        &mut self,
+        securable_type: SecurableType,
+        full_name: &str,
+        principal: &str,
+        privileges: &[Privilege],
+    ) -> Result<(), Error> {
+        let object: &mut SnapshotObject = self.object_mut(&securable_type, full_name)?;
+        for assignment in object
+            .privilege_assignments
+            .iter_mut()
+            .filter(|assignment| is_principal(assignment, principal))
+        {
+            if let Some(held) = assignment.privileges.as_mut() {
+                held.retain(|p| {
+                    !privileges
+                        .iter()
+                        .any(|r| p.eq_ignore_ascii_case(r.as_str()))
+                });
+            }
+        }
+        object
+            .privilege_assignments
+            .retain(|assignment| !assignment.privileges.as_ref().is_some_and(Vec::is_empty));
+        Ok(())
+    }
+
+    /// What if the owner of an object changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if the object is not in the snapshot.
+    pub fn set_owner(
+        &mut self,
+        securable_type: SecurableType,
+        full_name: &str,
+        owner: &str,
+    ) -> Result<(), Error> {
+        self.object_mut(&securable_type, full_name)?.owner = Some(owner.to_string());
+        Ok(())
+    }
+
+    /// What if a grants plan is applied, see `grants::plan`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Snapshot` if an object of the plan is not in the snapshot.
+    pub fn apply_plan(&mut self, plan: &Plan) -> Result<(), Error> {
+        for object in &plan.objects {
+            for change in &object.changes {
+                self.grant(
+                    object.securable_type.clone(),
+                    &object.full_name,
+                    &change.principal,
+                    &change.add,
+                )?;
+                self.revoke(
+                    object.securable_type.clone(),
+                    &object.full_name,
+                    &change.principal,
+                    &change.remove,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn object_mut(
+        &mut self,
+        securable_type: &SecurableType,
+        full_name: &str,
+    ) -> Result<&mut SnapshotObject, Error> {
+        self.objects
+            .iter_mut()
+            .find(|object| {
+                &object.securable_type == securable_type
+                    && object.full_name.eq_ignore_ascii_case(full_name)
+            })
+            .ok_or_else(|| not_in_snapshot(securable_type, full_name))
+    }
+
+    /// Builds the facts the evaluator needs for an object and its parents. The effective
+    /// privileges of each level are its direct grants plus the grants on its parents,
+    /// which is what the effective permissions API returns online.
+    fn levels(
+        &self,
+        securable_type: SecurableType,
+        full_name: &str,
+    ) -> Result<Vec<SecurableFacts>, Error> {
+        let objects: Vec<&SnapshotObject> = permissions::hierarchy(securable_type, full_name)
+            .iter()
+            .map(|(securable_type, name)| {
+                self.object(securable_type, name)
+                    .ok_or_else(|| not_in_snapshot(securable_type, name))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let levels: Vec<SecurableFacts> = objects
+            .iter()
+            .enumerate()
+            .map(|(depth, object)| {
+                let mut assignments: Vec<EffectivePrivilegeAssignment> = Vec::new();
+                for source in &objects[..=depth] {
+                    let inherited: bool = source.full_name != object.full_name;
+                    for grant in &source.privilege_assignments {
+                        let Some(principal) = grant.principal.clone() else {
+                            continue;
+                        };
+                        let privileges: Vec<EffectivePrivilege> = grant
+                            .privileges
+                            .iter()
+                            .flatten()
+                            .map(|privilege| EffectivePrivilege {
+                                privilege: privilege.clone(),
+                                inherited_from_type: inherited
+                                    .then(|| source.securable_type.clone()),
+                                inherited_from_name: inherited.then(|| source.full_name.clone()),
+                            })
+                            .collect();
+                        assignments.push(EffectivePrivilegeAssignment {
+                            principal,
+                            privileges,
+                        });
+                    }
+                }
+                SecurableFacts {
+                    securable_type: object.securable_type.clone(),
+                    full_name: object.full_name.clone(),
+                    owner: object.owner.clone(),
+                    privileges: EffectivePrivileges {
+                        securable_type: object.securable_type.clone(),
+                        full_name: object.full_name.clone(),
+                        assignments,
+                    },
+                }
+            })
+            .collect();
+        Ok(levels)
+    }
+}
+
+/// Returns true if the assignment is held by the principal, ignoring case like `Identity::is`.
+fn is_principal(assignment: &PrivilegeAssignment, principal: &str) -> bool {
+    assignment
+        .principal
+        .as_deref()
+        .is_some_and(|held| held.eq_ignore_ascii_case(principal))
+}
+
+fn not_in_snapshot(securable_type: &SecurableType, full_name: &str) -> Error {
+    Error::Snapshot(format!(
+        "{} {} is not in the snapshot",
+        securable_type, full_name
+    ))
+}
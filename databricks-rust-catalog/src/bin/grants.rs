@@ -0,0 +1,134 @@
+//! Plans and applies Unity Catalog grants from a declarative grants file, and reports who has
+//! access to what across the metastore.
+//!
+//! ```text
+//! grants plan <file> [--profile <name>]
+//! grants apply <file> [--profile <name>]
+//! grants report <output> [--format <csv|parquet|json>] [--concurrency <n>] [--profile <name>]
+//! ```
+//!
+//! The workspace and credentials are resolved like the library does, from the
+//! `~/.databrickscfg` profile overridden by environment variables.
+//!
+//! `report` records its progress in `<output>.checkpoint`, so running it again after a failure
+//! resumes the crawl. The checkpoint is removed once the report is written.
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::api::grants::{self, GrantsFile, Plan};
+use databricks_rust_catalog::api::report::{GrantsCrawler, GrantsReport, ReportFormat};
+use databricks_rust_catalog::config::{Config, ConfigLoader};
+use databricks_rust_catalog::{Error, Result};
+use dotenv::dotenv;
+
+const USAGE: &str = "usage: grants <plan|apply> <file> [--profile <name>]
+       grants report <output> [--format <csv|parquet|json>] [--concurrency <n>] [--profile <name>]";
+
+/// Parsed command line.
+struct Args<'a> {
+    command: &'a str,
+    file: &'a str,
+    profile: Option<&'a str>,
+    format: Option<ReportFormat>,
+    concurrency: Option<usize>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .parse_default_env()
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Args = parse_args(&args)?;
+
+    let mut loader: ConfigLoader = ConfigLoader::new();
+    if let Some(profile) = args.profile {
+        loader = loader.profile(profile);
+    }
+    let config: Config = loader.load()?;
+    let api_client: APIClient = APIClient::from_config(&config)?;
+
+    if args.command == "report" {
+        return report(api_client, &args).await;
+    }
+
+    let desired: GrantsFile = GrantsFile::load(args.file)?;
+    let plan: Plan = grants::plan(api_client.clone(), &desired).await?;
+    print!("{}", plan);
+
+    if args.command == "apply" && !plan.is_empty() {
+        grants::apply(api_client, &plan).await?;
+        let (add, remove) = plan.counts();
+        println!("Applied: {} granted, {} revoked.", add, remove);
+    }
+    Ok(())
+}
+
+/// Crawls the metastore into the output file, resuming from its checkpoint.
+async fn report(api_client: APIClient, args: &Args<'_>) -> Result<()> {
+    let format: ReportFormat = args
+        .format
+        .or_else(|| ReportFormat::from_path(args.file))
+        .unwrap_or(ReportFormat::Csv);
+    let checkpoint: String = format!("{}.checkpoint", args.file);
+
+    let mut crawler: GrantsCrawler = GrantsCrawler::new(api_client).checkpoint(&checkpoint);
+    if let Some(concurrency) = args.concurrency {
+        crawler = crawler.concurrency(concurrency);
+    }
+    let report: GrantsReport = crawler.crawl().await?;
+    report.write(args.file, format)?;
+    std::fs::remove_file(&checkpoint)
+        .map_err(|e| Error::Report(format!("unable to remove {}: {}", checkpoint, e)))?;
+    println!("Wrote {} grants to {}.", report.rows.len(), args.file);
+    if !report.skipped.is_empty() {
+        println!(
+            "Skipped {} catalogs and schemas whose children could not be listed.",
+            report.skipped.len()
+        );
+    }
+    Ok(())
+}
+
+/// Returns the command, its file and the options.
+fn parse_args(args: &[String]) -> Result<Args<'_>> {
+    let usage = || Error::Config(USAGE.to_string());
+    let mut positional: Vec<&str> = Vec::new();
+    let mut profile: Option<&str> = None;
+    let mut format: Option<ReportFormat> = None;
+    let mut concurrency: Option<usize> = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--profile" => profile = Some(iter.next().ok_or_else(usage)?),
+            "--format" => {
+                format = Some(iter.next().and_then(|f| f.parse().ok()).ok_or_else(usage)?)
+            }
+            "--concurrency" => {
+                concurrency = Some(iter.next().and_then(|n| n.parse().ok()).ok_or_else(usage)?)
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            arg => positional.push(arg),
+        }
+    }
+
+    let (command, file): (&str, &str) = match positional.as_slice() {
+        [command @ ("plan" | "apply"), file] if format.is_none() && concurrency.is_none() => {
+            (command, file)
+        }
+        [command @ "report", file] => (command, file),
+        _ => return Err(usage()),
+    };
+    Ok(Args {
+        command,
+        file,
+        profile,
+        format,
+        concurrency,
+    })
+}
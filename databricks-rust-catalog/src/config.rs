@@ -0,0 +1,395 @@
+use crate::api::auth::{
+    AzureServicePrincipalCredentials, CredentialProvider, OAuthM2MCredentials, PatCredentials,
+};
+use crate::error::Error;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Name of the profile used when none is requested.
+pub const DEFAULT_PROFILE: &str = "DEFAULT";
+
+/// Connection settings for a Databricks workspace.
+///
+/// Settings are resolved from a profile of the Databricks CLI configuration file
+/// (`~/.databrickscfg`), and any value set through an environment variable takes precedence
+/// over the profile. Missing required values are reported as `Error::Config` when they are used.
+///
+/// | Setting               | Profile key             | Environment variables                        |
+/// |-----------------------|-------------------------|----------------------------------------------|
+/// | `host`                | `host`                  | `DATABRICKS_HOST`, `WORKSPACE_NAME`          |
+/// | `token`               | `token`                 | `DATABRICKS_TOKEN`, `DB_TOKEN`               |
+/// | `client_id`           | `client_id`             | `DATABRICKS_CLIENT_ID`                       |
+/// | `client_secret`       | `client_secret`         | `DATABRICKS_CLIENT_SECRET`                   |
+/// | `azure_tenant_id`     | `azure_tenant_id`       | `ARM_TENANT_ID`                              |
+/// | `azure_client_id`     | `azure_client_id`       | `ARM_CLIENT_ID`                              |
+/// | `azure_client_secret` | `azure_client_secret`   | `ARM_CLIENT_SECRET`                          |
+/// | `auth_type`           | `auth_type`             | `DATABRICKS_AUTH_TYPE`                       |
+/// | `user_name`           | `user_name`             | `DATABRICKS_USER_NAME`, `USER_NAME`          |
+/// | `storage_url`         | `storage_url`           | `STORAGE_URL`                                |
+/// | `storage_port`        | `storage_port`          | `STORAGE_PORT`                               |
+/// | `storage_token`       | `storage_token`         | `STORAGE_TOKEN`                              |
+///
+/// When several environment variables map to the same setting the first one listed wins.
+#[derive(Clone, Default)]
+pub struct Config {
+    /// The profile the settings were read from, if a configuration file was used.
+    pub profile: Option<String>,
+    /// Workspace URL i.e. 'https://adb-12345678912345.11.azuredatabricks.net'.
+    pub host: Option<String>,
+    /// Personal access token.
+    pub token: Option<String>,
+    /// OAuth M2M client id of a Databricks service principal.
+    pub client_id: Option<String>,
+    /// OAuth M2M secret of a Databricks service principal.
+    pub client_secret: Option<String>,
+    /// Azure AD tenant of an Azure service principal.
+    pub azure_tenant_id: Option<String>,
+    /// Application id of an Azure service principal.
+    pub azure_client_id: Option<String>,
+    /// Client secret of an Azure service principal.
+    pub azure_client_secret: Option<String>,
+    /// Explicit authentication type: 'pat', 'oauth-m2m' or 'azure-client-secret'.
+    /// Inferred from the credentials that are set when missing.
+    pub auth_type: Option<String>,
+    /// The principal whose permissions are checked i.e. 'first.last@example.com'.
+    pub user_name: Option<String>,
+    /// URL of the storage credential service.
+    pub storage_url: Option<String>,
+    /// Port of the storage credential service.
+    pub storage_port: Option<String>,
+    /// Bearer token of the storage credential service.
+    pub storage_token: Option<String>,
+}
+
+impl Config {
+    /// Loads the profile named by `DATABRICKS_CONFIG_PROFILE` (or 'DEFAULT') from the
+    /// configuration file and applies environment overrides.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let config: Config = Config::load()?;
+    /// let api_client: APIClient = APIClient::from_config(&config)?;
+    /// ```
+    pub fn load() -> Result<Config, Error> {
+        ConfigLoader::new().load()
+    }
+
+    /// Loads a named profile from the configuration file and applies environment overrides.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The profile (section) name in `~/.databrickscfg`.
+    pub fn from_profile(profile: &str) -> Result<Config, Error> {
+        ConfigLoader::new().profile(profile).load()
+    }
+
+    /// Returns the workspace name, the host without scheme i.e. 'adb-12345678912345.11.azuredatabricks.net'.
+    pub fn workspace_name(&self) -> Result<String, Error> {
+        let host: &str = self.require(&self.host, "host")?;
+        let workspace_name: &str = host
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/');
+        Ok(workspace_name.to_string())
+    }
+
+    /// Returns the workspace URL including the scheme. Hosts without a scheme are assumed to use https.
+    pub fn base_url(&self) -> Result<String, Error> {
+        let host: &str = self.require(&self.host, "host")?;
+        if host.starts_with("http://") || host.starts_with("https://") {
+            Ok(host.trim_end_matches('/').to_string())
+        } else {
+            Ok(format!("https://{}", host.trim_end_matches('/')))
+        }
+    }
+
+    /// Returns the principal whose permissions are checked.
+    pub fn user_name(&self) -> Result<String, Error> {
+        self.require(&self.user_name, "user_name").map(String::from)
+    }
+
+    /// Returns the storage credential service endpoint as `{storage_url}:{storage_port}`.
+    pub fn storage_endpoint(&self) -> Result<String, Error> {
+        let storage_url: &str = self.require(&self.storage_url, "storage_url")?;
+        let storage_port: &str = self.require(&self.storage_port, "storage_port")?;
+        Ok(format!("{}:{}", storage_url, storage_port))
+    }
+
+    /// Returns the bearer token of the storage credential service.
+    pub fn storage_token(&self) -> Result<String, Error> {
+        self.require(&self.storage_token, "storage_token")
+            .map(String::from)
+    }
+
+    /// Returns the authentication type, either set explicitly or inferred from the credentials present.
+    pub fn resolved_auth_type(&self) -> Result<String, Error> {
+        if let Some(auth_type) = &self.auth_type {
+            return Ok(auth_type.clone());
+        }
+        if self.token.is_some() {
+            Ok("pat".to_string())
+        } else if self.azure_client_secret.is_some() {
+            Ok("azure-client-secret".to_string())
+        } else if self.client_secret.is_some() {
+            Ok("oauth-m2m".to_string())
+        } else {
+            Err(Error::Config(format!(
+                "no credentials configured{}: set a token, client_id/client_secret or azure_client_id/azure_client_secret",
+                self.profile_suffix()
+            )))
+        }
+    }
+
+    /// Builds the credential provider matching the resolved authentication type.
+    pub fn credentials(&self) -> Result<Arc<dyn CredentialProvider>, Error> {
+        let auth_type: String = self.resolved_auth_type()?;
+        match auth_type.as_str() {
+            "pat" => {
+                let token: &str = self.require(&self.token, "token")?;
+                Ok(Arc::new(PatCredentials::new(token.to_string())))
+            }
+            "oauth-m2m" => {
+                let client_id: &str = self.require(&self.client_id, "client_id")?;
+                let client_secret: &str = self.require(&self.client_secret, "client_secret")?;
+                Ok(Arc::new(OAuthM2MCredentials::new(
+                    &self.base_url()?,
+                    client_id.to_string(),
+                    client_secret.to_string(),
+                )))
+            }
+            "azure-client-secret" => {
+                let tenant_id: &str = self.require(&self.azure_tenant_id, "azure_tenant_id")?;
+                // Azure service principals are commonly configured through the generic client id
+                let client_id: &str = match &self.azure_client_id {
+                    Some(client_id) => client_id,
+                    None => self.require(&self.client_id, "azure_client_id")?,
+                };
+                let client_secret: &str =
+                    self.require(&self.azure_client_secret, "azure_client_secret")?;
+                Ok(Arc::new(AzureServicePrincipalCredentials::new(
+                    tenant_id.to_string(),
+                    client_id.to_string(),
+                    client_secret.to_string(),
+                )))
+            }
+            other => Err(Error::Config(format!(
+                "unsupported auth_type '{}'{}",
+                other,
+                self.profile_suffix()
+            ))),
+        }
+    }
+
+    fn require<'a>(&self, value: &'a Option<String>, name: &str) -> Result<&'a str, Error> {
+        value
+            .as_deref()
+            .ok_or_else(|| Error::Config(format!("{} is not set{}", name, self.profile_suffix())))
+    }
+
+    fn profile_suffix(&self) -> String {
+        match &self.profile {
+            Some(profile) => format!(" (profile {})", profile),
+            None => String::new(),
+        }
+    }
+
+    /// Sets a setting from a profile key, ignoring unknown keys.
+    fn set(&mut self, key: &str, value: String) {
+        let field: &mut Option<String> = match key {
+            "host" => &mut self.host,
+            "token" => &mut self.token,
+            "client_id" => &mut self.client_id,
+            "client_secret" => &mut self.client_secret,
+            "azure_tenant_id" => &mut self.azure_tenant_id,
+            "azure_client_id" => &mut self.azure_client_id,
+            "azure_client_secret" => &mut self.azure_client_secret,
+            "auth_type" => &mut self.auth_type,
+            "user_name" => &mut self.user_name,
+            "storage_url" => &mut self.storage_url,
+            "storage_port" => &mut self.storage_port,
+            "storage_token" => &mut self.storage_token,
+            _ => return,
+        };
+        *field = Some(value);
+    }
+}
+
+// secrets are redacted so configs can be logged safely
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redact = |value: &Option<String>| value.as_ref().map(|_| "***");
+        f.debug_struct("Config")
+            .field("profile", &self.profile)
+            .field("host", &self.host)
+            .field("token", &redact(&self.token))
+            .field("client_id", &self.client_id)
+            .field("client_secret", &redact(&self.client_secret))
+            .field("azure_tenant_id", &self.azure_tenant_id)
+            .field("azure_client_id", &self.azure_client_id)
+            .field("azure_client_secret", &redact(&self.azure_client_secret))
+            .field("auth_type", &self.auth_type)
+            .field("user_name", &self.user_name)
+            .field("storage_url", &self.storage_url)
+            .field("storage_port", &self.storage_port)
+            .field("storage_token", &redact(&self.storage_token))
+            .finish()
+    }
+}
+
+/// Environment variables in order of precedence for each profile key.
+const ENV_OVERRIDES: &[(&str, &[&str])] = &[
+    ("host", &["DATABRICKS_HOST", "WORKSPACE_NAME"]),
+    ("token", &["DATABRICKS_TOKEN", "DB_TOKEN"]),
+    ("client_id", &["DATABRICKS_CLIENT_ID"]),
+    ("client_secret", &["DATABRICKS_CLIENT_SECRET"]),
+    ("azure_tenant_id", &["ARM_TENANT_ID"]),
+    ("azure_client_id", &["ARM_CLIENT_ID"]),
+    ("azure_client_secret", &["ARM_CLIENT_SECRET"]),
+    ("auth_type", &["DATABRICKS_AUTH_TYPE"]),
+    ("user_name", &["DATABRICKS_USER_NAME", "USER_NAME"]),
+    ("storage_url", &["STORAGE_URL"]),
+    ("storage_port", &["STORAGE_PORT"]),
+    ("storage_token", &["STORAGE_TOKEN"]),
+];
+
+/// Resolves a `Config` from a configuration file profile and environment variables.
+///
+/// # Examples
+///
+/// ```ignore
+/// let config: Config = ConfigLoader::new()
+///     .profile("ci")
+///     .config_file("/etc/databricks/databrickscfg")
+///     .load()?;
+/// ```
+#[derive(Default)]
+pub struct ConfigLoader {
+    profile: Option<String>,
+    config_file: Option<PathBuf>,
+    env: Option<HashMap<String, String>>,
+}
+
+impl ConfigLoader {
+    /// Creates a loader that reads the process environment and the default configuration file.
+    pub fn new() -> Self {
+        ConfigLoader::default()
+    }
+
+    /// Selects the profile to read. Defaults to `DATABRICKS_CONFIG_PROFILE` or 'DEFAULT'.
+    pub fn profile(mut self, profile: &str) -> Self {
+        self.profile = Some(profile.to_string());
+        self
+    }
+
+    /// Reads the given configuration file instead of `DATABRICKS_CONFIG_FILE` or `~/.databrickscfg`.
+    pub fn config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    /// Uses the given variables instead of the process environment.
+    pub fn env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// Resolves the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Config` if the configuration file cannot be read or parsed, or if a
+    /// profile was requested explicitly and does not exist. A missing default profile is not
+    /// an error so environment-only setups keep working.
+    pub fn load(self) -> Result<Config, Error> {
+        let env: HashMap<String, String> = self.env.unwrap_or_else(|| std::env::vars().collect());
+        let env_var = |name: &str| env.get(name).filter(|value| !value.is_empty()).cloned();
+
+        let explicit_profile: Option<String> = self
+            .profile
+            .or_else(|| env_var("DATABRICKS_CONFIG_PROFILE"));
+        let profile: String = explicit_profile
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        let config_file: Option<PathBuf> = self
+            .config_file
+            .or_else(|| env_var("DATABRICKS_CONFIG_FILE").map(PathBuf::from))
+            .or_else(|| {
+                env_var("HOME")
+                    .or_else(|| env_var("USERPROFILE"))
+                    .map(|home| PathBuf::from(home).join(".databrickscfg"))
+            });
+
+        let mut config: Config = Config::default();
+        let sections: HashMap<String, HashMap<String, String>> = match &config_file {
+            Some(path) if path.exists() => {
+                let contents: String = std::fs::read_to_string(path).map_err(|e| {
+                    Error::Config(format!("unable to read {}: {}", path.display(), e))
+                })?;
+                parse_ini(&contents).map_err(|msg| {
+                    Error::Config(format!("unable to parse {}: {}", path.display(), msg))
+                })?
+            }
+            _ => HashMap::new(),
+        };
+
+        match sections.get(&profile) {
+            Some(section) => {
+                log::debug!("Loading Databricks profile {}", profile);
+                config.profile = Some(profile);
+                for (key, value) in section {
+                    config.set(key, value.clone());
+                }
+            }
+            None if explicit_profile.is_some() => {
+                let location: String = config_file
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_else(|| "~/.databrickscfg".to_string());
+                return Err(Error::Config(format!(
+                    "profile {} not found in {}",
+                    profile, location
+                )));
+            }
+            None => {}
+        }
+
+        for (key, names) in ENV_OVERRIDES {
+            if let Some(value) = names.iter().find_map(|name| env_var(name)) {
+                config.set(key, value);
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Parses an INI document into sections of lower cased keys.
+fn parse_ini(contents: &str) -> Result<HashMap<String, HashMap<String, String>>, String> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for (number, line) in contents.lines().enumerate() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name: String = name.trim().to_string();
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected 'key = value'", number + 1));
+        };
+        let Some(section) = &current else {
+            return Err(format!("line {}: key outside of a [profile]", number + 1));
+        };
+        sections
+            .get_mut(section)
+            .expect("section was inserted when its header was read")
+            .insert(key.trim().to_lowercase(), value.trim().to_string());
+    }
+    Ok(sections)
+}
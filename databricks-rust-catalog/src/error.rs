@@ -0,0 +1,240 @@
+use deltalake::datafusion::error::DataFusionError;
+use deltalake::{DeltaTableError, ObjectStoreError};
+use polars::prelude::PolarsError;
+use serde::Deserialize;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Convenience alias for results returned by this crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Crate-wide error type.
+///
+/// Every public function in the crate returns this error so callers can
+/// distinguish a permission problem from a missing table or a broken file
+/// without matching on strings.
+#[derive(Debug)]
+pub enum Error {
+    /// Transport level failure (connection refused, timeout, TLS, invalid JSON body, ...).
+    Http(reqwest::Error),
+    /// A request URL is not valid, i.e. because the `base_url` of the client is malformed.
+    InvalidUrl(String),
+    /// The Databricks REST API returned an error payload.
+    Api(ApiError),
+    /// A request or response body could not be serialized or deserialized.
+    Json(serde_json::Error),
+    /// The principal could not be authenticated against the workspace.
+    AuthenticationFailed(String),
+    /// The token belongs to another principal than the one the caller claimed to be.
+    IdentityMismatch {
+        /// The principal the caller claimed to be.
+        claimed: String,
+        /// The principal the token actually belongs to.
+        actual: String,
+    },
+    /// The principal does not hold the privileges required for the operation.
+    PermissionDenied(String),
+    /// The requested table does not exist in Unity Catalog.
+    TableNotFound(String),
+    /// The table exists but Unity Catalog did not return a storage location for it.
+    MissingStorageLocation(String),
+    /// Storage credentials could not be obtained or decrypted, or a bearer token could not be
+    /// sent as a header.
+    Credentials(String),
+    /// The client configuration is invalid or incomplete.
+    Config(String),
+    /// A file could not be read or written.
+    Io {
+        /// The file.
+        path: PathBuf,
+        /// Why it could not be read or written.
+        source: std::io::Error,
+    },
+    /// A grants file is not valid TOML or names an unknown securable type or privilege.
+    InvalidGrants {
+        /// Line of the error, starting at 1, if known.
+        line: Option<usize>,
+        /// Column of the error, starting at 1, if known.
+        column: Option<usize>,
+        /// What is wrong.
+        message: String,
+    },
+    /// The audit log could not be read or written.
+    Audit(String),
+    /// A record of the audit log was modified, inserted or removed.
+    AuditTampered {
+        /// Line of the first record that does not verify, starting at 1.
+        line: usize,
+        /// Why the record does not verify.
+        reason: String,
+    },
+    /// A grants report or its checkpoint could not be read or written.
+    Report(String),
+    /// A grants snapshot could not be read or written, or does not contain an object.
+    Snapshot(String),
+    /// Failure while talking to the underlying object store.
+    Storage(ObjectStoreError),
+    /// Failure while opening or reading a Delta table.
+    Delta(DeltaTableError),
+    /// Failure while building a Polars dataframe.
+    Polars(PolarsError),
+    /// Failure while building or executing a DataFusion plan.
+    DataFusion(DataFusionError),
+}
+
+/// Error returned by the Databricks REST API.
+/// Refer to: https://docs.databricks.com/en/dev-tools/api/latest/error-handling.html
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ApiError {
+    /// HTTP status code of the failed response.
+    #[serde(skip)]
+    pub status: u16,
+    /// Databricks error code i.e. `TABLE_DOES_NOT_EXIST` or `PERMISSION_DENIED`.
+    pub error_code: Option<String>,
+    /// Human readable error message.
+    #[serde(default)]
+    pub message: String,
+    /// The URL of the request that failed.
+    #[serde(skip)]
+    pub url: String,
+}
+
+impl ApiError {
+    /// Builds an `ApiError` from the body of a failed response.
+    /// Bodies that are not a Databricks error payload are kept verbatim as the message.
+    pub fn from_body(status: u16, url: &str, body: &str) -> Self {
+        let mut api_error: ApiError = serde_json::from_str(body).unwrap_or(ApiError {
+            status,
+            error_code: None,
+            message: body.to_string(),
+            url: String::new(),
+        });
+        api_error.status = status;
+        api_error.url = url.to_string();
+        api_error
+    }
+
+    /// Returns true if the API reported that the requested object does not exist.
+    pub fn is_not_found(&self) -> bool {
+        self.status == 404
+            || matches!(
+                self.error_code.as_deref(),
+                Some("RESOURCE_DOES_NOT_EXIST" | "NOT_FOUND" | "TABLE_DOES_NOT_EXIST")
+            )
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "request to {} failed with status {} ({}): {}",
+            self.url,
+            self.status,
+            self.error_code.as_deref().unwrap_or("UNKNOWN"),
+            self.message
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "HTTP error: {}", e),
+            Error::InvalidUrl(msg) => write!(f, "Invalid URL: {}", msg),
+            Error::Api(e) => write!(f, "Databricks API error: {}", e),
+            Error::Json(e) => write!(f, "JSON error: {}", e),
+            Error::AuthenticationFailed(msg) => write!(f, "Authentication failed: {}", msg),
+            Error::IdentityMismatch { claimed, actual } => write!(
+                f,
+                "Identity mismatch: claimed to be {} but the token belongs to {}",
+                claimed, actual
+            ),
+            Error::PermissionDenied(msg) => write!(f, "Permission denied: {}", msg),
+            Error::TableNotFound(name) => write!(f, "Table not found: {}", name),
+            Error::MissingStorageLocation(name) => {
+                write!(f, "Table location not found for: {}", name)
+            }
+            Error::Credentials(msg) => write!(f, "Credentials error: {}", msg),
+            Error::Config(msg) => write!(f, "Configuration error: {}", msg),
+            Error::Io { path, source } => write!(f, "I/O error on {}: {}", path.display(), source),
+            Error::InvalidGrants {
+                line: Some(line),
+                column: Some(column),
+                message,
+            } => write!(
+                f,
+                "Invalid grants file at line {}, column {}: {}",
+                line, column, message
+            ),
+            Error::InvalidGrants { message, .. } => write!(f, "Invalid grants file: {}", message),
+            Error::Audit(msg) => write!(f, "Audit log error: {}", msg),
+            Error::AuditTampered { line, reason } => {
+                write!(f, "Audit log tampered at line {}: {}", line, reason)
+            }
+            Error::Report(msg) => write!(f, "Report error: {}", msg),
+            Error::Snapshot(msg) => write!(f, "Snapshot error: {}", msg),
+            Error::Storage(e) => write!(f, "Object store error: {}", e),
+            Error::Delta(e) => write!(f, "Delta error: {}", e),
+            Error::Polars(e) => write!(f, "Polars error: {}", e),
+            Error::DataFusion(e) => write!(f, "DataFusion error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Io { source, .. } => Some(source),
+            Error::Storage(e) => Some(e),
+            Error::Delta(e) => Some(e),
+            Error::Polars(e) => Some(e),
+            Error::DataFusion(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(e: ApiError) -> Self {
+        Error::Api(e)
+    }
+}
+
+impl From<ObjectStoreError> for Error {
+    fn from(e: ObjectStoreError) -> Self {
+        Error::Storage(e)
+    }
+}
+
+impl From<DeltaTableError> for Error {
+    fn from(e: DeltaTableError) -> Self {
+        Error::Delta(e)
+    }
+}
+
+impl From<PolarsError> for Error {
+    fn from(e: PolarsError) -> Self {
+        Error::Polars(e)
+    }
+}
+
+impl From<DataFusionError> for Error {
+    fn from(e: DataFusionError) -> Self {
+        Error::DataFusion(e)
+    }
+}
@@ -1,41 +1,49 @@
 #![warn(unused_extern_crates)]
 use dotenv::dotenv;
-use log;
-use std::env;
-use std::error::Error;
+pub mod config;
+pub mod error;
 pub mod api {
     pub mod api_client;
+    pub mod audit;
+    pub mod auth;
+    pub mod cache;
     pub mod delta;
+    pub mod evaluator;
+    pub mod grants;
     pub mod metastore;
+    pub mod pagination;
     pub mod permissions;
+    pub mod report;
+    pub mod retry;
+    pub mod snapshot;
 }
 
 use api::delta::DeltaLakeManager;
+use config::Config;
+pub use error::{Error, Result};
 
+// main.rs doubles as the library root, where `main` is never called
+#[allow(dead_code)]
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> Result<()> {
     dotenv().ok();
 
     env_logger::builder()
         .filter_level(log::LevelFilter::Info)
         .init();
 
-    let db_token: String = env::var("DB_TOKEN").expect("DB_TOKEN not set");
-    let workspace_name: String = env::var("WORKSPACE_NAME").expect("WORKSPACE_NAME not set");
-    let principal: String = env::var("USER_NAME").expect("USER_NAME not set");
+    // ~/.databrickscfg profile, overridden by environment variables
+    let config: Config = Config::load()?;
 
     // Data Reading
-    let reader: DeltaLakeManager =
-        DeltaLakeManager::new(principal, db_token, workspace_name).await?;
+    let reader: DeltaLakeManager = DeltaLakeManager::from_config(&config).await?;
 
     let table_name: &str = "rac_demo_catalog.rust_schema.dbu_forecasts";
 
     // let df = reader.read_delta_table_as_datafusion(table_path).await.unwrap();
 
-    let pdf: polars::prelude::DataFrame = reader
-        .read_delta_table_as_polars(table_name, true)
-        .await
-        .unwrap();
+    let pdf: polars::prelude::DataFrame =
+        reader.read_delta_table_as_polars(table_name, true).await?;
     println!("{}", pdf);
 
     Ok(())
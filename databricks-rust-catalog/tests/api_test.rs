@@ -1,18 +1,250 @@
-use databricks_rust_catalog::api::api_client::APIClient;
+mod common;
 
-use std::env;
-use dotenv::dotenv;
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_fetch_method() {
-    dotenv().ok();
-    let db_token: String = env::var("DB_TOKEN").expect("DB_TOKEN not set");
-    let workspace_name: String = env::var("WORKSPACE_NAME").expect("WORKSPACE_NAME not set");
+    let router: Router = Router::new().route(
+        "/api/2.0/preview/scim/v2/Me",
+        get(|headers: HeaderMap| async move {
+            if headers["authorization"] != "Bearer token" {
+                return (StatusCode::UNAUTHORIZED, Json(json!({}))).into_response();
+            }
+            Json(common::user_json("first.last@example.com")).into_response()
+        }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
 
-    let client = APIClient{ db_token: db_token.clone(), workspace_name: workspace_name.clone() }; 
-    let url: String = format!("https://{}/api/2.0/preview/scim/v2/Me", workspace_name.clone());
+    let client: APIClient = APIClient::builder("localhost".to_string(), "token".to_string())
+        .base_url(&base_url)
+        .build()
+        .unwrap();
+    let url: String = client.url("/api/2.0/preview/scim/v2/Me");
     let response = client.fetch(&url, None).await.unwrap();
     let status: bool = response.status().is_success();
     log::info!("Fetch Status: {}", status);
     assert!(status);
 }
+
+#[test]
+fn test_url_resolves_against_base_url() {
+    let default_client: APIClient = APIClient::new(
+        "adb-123.azuredatabricks.net".to_string(),
+        "token".to_string(),
+    );
+    assert_eq!(
+        default_client.url("/api/2.1/unity-catalog/catalogs"),
+        "https://adb-123.azuredatabricks.net/api/2.1/unity-catalog/catalogs"
+    );
+
+    let prefixed_client: APIClient =
+        APIClient::builder("localhost".to_string(), "token".to_string())
+            .base_url("http://127.0.0.1:8080/proxy/")
+            .build()
+            .unwrap();
+    assert_eq!(
+        prefixed_client.url("api/2.1/unity-catalog/catalogs"),
+        "http://127.0.0.1:8080/proxy/api/2.1/unity-catalog/catalogs"
+    );
+    assert_eq!(prefixed_client.url("http://other:1/x"), "http://other:1/x");
+}
+
+#[tokio::test]
+async fn test_fetch_returns_api_error_without_resending() {
+    let hits: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let router: Router = Router::new().route(
+        "/api/2.1/unity-catalog/tables/main.default.missing",
+        get(move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error_code": "TABLE_DOES_NOT_EXIST", "message": "Table 'main.default.missing' does not exist."})),
+            )
+        }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+
+    let client = APIClient::new("localhost".to_string(), "token".to_string());
+    let url: String = format!(
+        "{}/api/2.1/unity-catalog/tables/main.default.missing",
+        base_url
+    );
+    let err: Error = client.fetch(&url, None).await.unwrap_err();
+
+    match err {
+        Error::Api(api_error) => {
+            assert_eq!(api_error.status, 404);
+            assert_eq!(
+                api_error.error_code.as_deref(),
+                Some("TABLE_DOES_NOT_EXIST")
+            );
+            assert_eq!(
+                api_error.message,
+                "Table 'main.default.missing' does not exist."
+            );
+            assert_eq!(api_error.url, url);
+        }
+        other => panic!("expected an API error, got {:?}", other),
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_builder_applies_user_agent_and_reuses_client() {
+    let router: Router = Router::new().route(
+        "/api/2.0/preview/scim/v2/Me",
+        get(|headers: HeaderMap| async move {
+            let user_agent: String = headers["user-agent"].to_str().unwrap().to_string();
+            Json(json!({"userAgent": user_agent}))
+        }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+
+    let client: APIClient = APIClient::builder("localhost".to_string(), "token".to_string())
+        .user_agent("catalog-tests/1.0")
+        .connect_timeout(Duration::from_secs(2))
+        .request_timeout(Duration::from_secs(5))
+        .pool_max_idle_per_host(2)
+        .build()
+        .unwrap();
+    let url: String = format!("{}/api/2.0/preview/scim/v2/Me", base_url);
+
+    for _ in 0..3 {
+        let body: serde_json::Value = client
+            .fetch(&url, None)
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["userAgent"], "catalog-tests/1.0");
+    }
+}
+
+#[tokio::test]
+async fn test_builder_rejects_missing_ca_bundle() {
+    let result = APIClient::builder("localhost".to_string(), "token".to_string())
+        .ca_bundle("/does/not/exist.pem")
+        .build();
+
+    assert!(matches!(result, Err(Error::Config(_))));
+}
+
+#[derive(Serialize)]
+struct CommentUpdate<'a> {
+    comment: &'a str,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Echo {
+    method: String,
+    comment: Option<String>,
+}
+
+#[tokio::test]
+async fn test_typed_http_verbs() {
+    async fn echo(
+        method: axum::http::Method,
+        body: Option<Json<serde_json::Value>>,
+    ) -> Json<serde_json::Value> {
+        let comment = body.and_then(|Json(b)| b["comment"].as_str().map(|c| c.to_string()));
+        Json(json!({"method": method.as_str(), "comment": comment}))
+    }
+    let router: Router = Router::new().route(
+        "/api/2.1/unity-catalog/tables/main.sales.orders",
+        post(echo)
+            .patch(echo)
+            .put(echo)
+            .get(echo)
+            .delete(|| async { StatusCode::OK }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+    let client: APIClient = APIClient::new("localhost".to_string(), "token".to_string());
+    let url: String = format!(
+        "{}/api/2.1/unity-catalog/tables/main.sales.orders",
+        base_url
+    );
+    let body = CommentUpdate { comment: "orders" };
+
+    let posted: Echo = client.post(&url, &body).await.unwrap();
+    let patched: Echo = client.patch(&url, &body).await.unwrap();
+    let put: Echo = client.put(&url, &body).await.unwrap();
+    let fetched: Echo = client.get(&url).await.unwrap();
+    client.delete(&url).await.unwrap();
+
+    assert_eq!(
+        posted,
+        Echo {
+            method: "POST".to_string(),
+            comment: Some("orders".to_string())
+        }
+    );
+    assert_eq!(patched.method, "PATCH");
+    assert_eq!(put.method, "PUT");
+    assert_eq!(
+        fetched,
+        Echo {
+            method: "GET".to_string(),
+            comment: None
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_empty_response_body() {
+    let router: Router = Router::new().route(
+        "/api/2.1/unity-catalog/tables/main.sales.orders",
+        post(|| async { StatusCode::NO_CONTENT }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+    let client: APIClient = common::api_client(&base_url);
+    let path: &str = "/api/2.1/unity-catalog/tables/main.sales.orders";
+    let body = CommentUpdate { comment: "orders" };
+
+    let unit: Result<(), Error> = client.post(path, &body).await;
+    let optional: Result<Option<Echo>, Error> = client.post(path, &body).await;
+    let echo: Result<Echo, Error> = client.post(path, &body).await;
+
+    assert!(unit.is_ok());
+    assert!(matches!(optional, Ok(None)));
+    assert!(matches!(echo, Err(Error::Json(_))));
+}
+
+#[tokio::test]
+async fn test_post_returns_api_error() {
+    let router: Router = Router::new().route(
+        "/api/2.1/unity-catalog/schemas",
+        post(|| async {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error_code": "SCHEMA_ALREADY_EXISTS", "message": "Schema 'sales' already exists"})),
+            )
+        }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+    let client: APIClient = APIClient::new("localhost".to_string(), "token".to_string());
+
+    let result: Result<serde_json::Value, Error> = client
+        .post(
+            &format!("{}/api/2.1/unity-catalog/schemas", base_url),
+            &json!({"name": "sales"}),
+        )
+        .await;
+
+    assert!(
+        matches!(result, Err(Error::Api(ref e)) if e.error_code.as_deref() == Some("SCHEMA_ALREADY_EXISTS"))
+    );
+}
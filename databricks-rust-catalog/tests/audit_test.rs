@@ -0,0 +1,312 @@
+mod common;
+
+use axum::{extract::Path, routing::get, Json, Router};
+use databricks_rust_catalog::api::audit::{
+    self, AuditAction, AuditEvent, AuditLogSummary, AuditRecord, AuditSink, JsonLinesAuditSink,
+    GENESIS_HASH,
+};
+use databricks_rust_catalog::api::delta::DeltaLakeManager;
+use databricks_rust_catalog::Error;
+use deltalake::arrow::array::Int32Array;
+use deltalake::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use deltalake::arrow::record_batch::RecordBatch;
+use deltalake::datafusion::prelude::{DataFrame, SessionContext};
+use deltalake::{DeltaOps, DeltaTable};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const USER: &str = "first.last@example.com";
+
+/// Empty log path in the temp directory, unique per test.
+fn log_path(name: &str) -> PathBuf {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("audit-{}-{}.jsonl", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn read_records(path: &PathBuf) -> Vec<AuditRecord> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+/// Writes three records: an allowed read, a denied write and an allowed write.
+fn write_log(path: &PathBuf) {
+    let sink: JsonLinesAuditSink = JsonLinesAuditSink::open(path).unwrap();
+    sink.record(
+        AuditEvent::new(USER, AuditAction::Read, "main.sales.orders", true)
+            .table_version(7)
+            .bytes(4096),
+    )
+    .unwrap();
+    sink.record(AuditEvent::new(
+        USER,
+        AuditAction::Write,
+        "main.sales.orders",
+        false,
+    ))
+    .unwrap();
+    sink.record(
+        AuditEvent::new(USER, AuditAction::Write, "main.sales.customers", true).table_version(2),
+    )
+    .unwrap();
+}
+
+fn tampered_line(path: &PathBuf) -> usize {
+    match audit::verify_audit_log(path) {
+        Err(Error::AuditTampered { line, .. }) => line,
+        other => panic!("expected a tampered log, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_records_are_hash_chained() {
+    let path: PathBuf = log_path("chain");
+    write_log(&path);
+
+    let records: Vec<AuditRecord> = read_records(&path);
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].sequence, 1);
+    assert_eq!(records[0].prev_hash, GENESIS_HASH);
+    assert_eq!(records[0].table_version, Some(7));
+    assert_eq!(records[0].bytes, Some(4096));
+    assert_eq!(records[1].prev_hash, records[0].hash);
+    assert!(!records[1].allowed);
+    assert_eq!(records[2].prev_hash, records[1].hash);
+
+    let summary: AuditLogSummary = audit::verify_audit_log(&path).unwrap();
+    assert_eq!(summary.records, 3);
+    assert_eq!(summary.last_hash, records[2].hash);
+
+    // reopening continues the chain
+    let sink: JsonLinesAuditSink = JsonLinesAuditSink::open(&path).unwrap();
+    sink.record(AuditEvent::new(
+        USER,
+        AuditAction::Read,
+        "main.hr.salaries",
+        false,
+    ))
+    .unwrap();
+    let records: Vec<AuditRecord> = read_records(&path);
+    assert_eq!(records[3].sequence, 4);
+    assert_eq!(records[3].prev_hash, records[2].hash);
+    assert_eq!(audit::verify_audit_log(&path).unwrap().records, 4);
+}
+
+#[test]
+fn test_edits_are_detected() {
+    let path: PathBuf = log_path("edits");
+    write_log(&path);
+    let original: String = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = original.lines().collect();
+
+    // flipping a denied write to allowed
+    std::fs::write(
+        &path,
+        original.replacen("\"allowed\":false", "\"allowed\":true", 1),
+    )
+    .unwrap();
+    assert_eq!(tampered_line(&path), 2);
+
+    // removing a record
+    std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+    assert_eq!(tampered_line(&path), 2);
+
+    // rewriting a record and its hash breaks the link from the next record
+    let mut forged: AuditRecord = serde_json::from_str(lines[0]).unwrap();
+    forged.bytes = Some(1);
+    forged.hash = forged.compute_hash().unwrap();
+    std::fs::write(
+        &path,
+        format!(
+            "{}\n{}\n{}\n",
+            serde_json::to_string(&forged).unwrap(),
+            lines[1],
+            lines[2]
+        ),
+    )
+    .unwrap();
+    assert_eq!(tampered_line(&path), 2);
+
+    // extra fields are not covered by the hash but still rejected
+    std::fs::write(
+        &path,
+        original.replacen("{\"sequence\":3", "{\"note\":\"ok\",\"sequence\":3", 1),
+    )
+    .unwrap();
+    assert_eq!(tampered_line(&path), 3);
+
+    // a tampered log is not extended
+    assert!(matches!(
+        JsonLinesAuditSink::open(&path),
+        Err(Error::AuditTampered { line: 3, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_denied_reads_are_audited() {
+    // every object is owned by admins and nothing is granted to the user
+    let app: Router =
+        Router::new()
+            .route(
+                "/api/2.0/preview/scim/v2/Me",
+                get(|| async { Json(common::user_json(USER)) }),
+            )
+            .route(
+                "/api/2.1/unity-catalog/catalogs/:name",
+                get(|Path(name): Path<String>| async move {
+                    Json(common::catalog_json(&name, "admins"))
+                }),
+            )
+            .route(
+                "/api/2.1/unity-catalog/schemas/:full_name",
+                get(|Path(full_name): Path<String>| async move {
+                    let (catalog, schema) = full_name.split_once('.').unwrap();
+                    Json(common::schema_json(catalog, schema, "admins"))
+                }),
+            )
+            .route(
+                "/api/2.1/unity-catalog/tables/:full_name",
+                get(|Path(full_name): Path<String>| async move {
+                    let parts: Vec<&str> = full_name.split('.').collect();
+                    Json(common::table_json(parts[0], parts[1], parts[2], "admins"))
+                }),
+            )
+            .route(
+                "/api/2.1/unity-catalog/effective-permissions/:securable_type/:full_name",
+                get(|| async { Json(json!({ "privilege_assignments": [] })) }),
+            );
+    let base_url: String = common::spawn_stub(app).await;
+
+    let path: PathBuf = log_path("reader");
+    let sink: Arc<JsonLinesAuditSink> = Arc::new(JsonLinesAuditSink::open(&path).unwrap());
+    let reader: DeltaLakeManager = DeltaLakeManager::builder()
+        .principal(USER)
+        .api_client(common::api_client(&base_url))
+        .storage_option("azure_storage_sas_key", "sv=2022-11-02")
+        .audit_sink(sink.clone())
+        .build()
+        .await
+        .unwrap();
+
+    let result = reader
+        .read_delta_table_as_datafusion("main.sales.orders")
+        .await;
+    assert!(matches!(result, Err(Error::PermissionDenied(_))));
+
+    let result = reader
+        .read_delta_table_as_polars("main.sales.customers", false)
+        .await;
+    assert!(
+        matches!(result, Err(Error::PermissionDenied(table)) if table == "main.sales.customers")
+    );
+
+    let records: Vec<AuditRecord> = read_records(&path);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].principal, USER);
+    assert_eq!(records[0].action, AuditAction::Read);
+    assert_eq!(records[0].table, "main.sales.orders");
+    assert!(!records[0].allowed);
+    assert_eq!(records[0].table_version, None);
+    assert_eq!(records[1].table, "main.sales.customers");
+    assert!(!records[1].allowed);
+    assert_eq!(audit::verify_audit_log(sink.path()).unwrap().records, 2);
+}
+
+#[tokio::test]
+async fn test_writes_are_audited_after_the_commit() {
+    // the user owns every object and the table lives in a local directory
+    let table_dir: PathBuf =
+        std::env::temp_dir().join(format!("audit-table-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&table_dir);
+    std::fs::create_dir_all(&table_dir).unwrap();
+    let table_path: String = table_dir.to_str().unwrap().to_string();
+    let schema: SchemaRef = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+    let batch: RecordBatch =
+        RecordBatch::try_new(schema, vec![Arc::new(Int32Array::from(vec![1, 2, 3]))]).unwrap();
+    let table: DeltaTable = DeltaOps::try_from_uri(&table_path)
+        .await
+        .unwrap()
+        .write(vec![batch.clone()])
+        .await
+        .unwrap();
+    assert_eq!(table.version(), 0);
+    let parquet_bytes = || -> u64 {
+        std::fs::read_dir(&table_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "parquet")
+            })
+            .map(|path| std::fs::metadata(path).unwrap().len())
+            .sum()
+    };
+    let existing: u64 = parquet_bytes();
+
+    let location: String = table_path.clone();
+    let app: Router = Router::new()
+        .route(
+            "/api/2.0/preview/scim/v2/Me",
+            get(|| async { Json(common::user_json(USER)) }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/catalogs/:name",
+            get(|Path(name): Path<String>| async move { Json(common::catalog_json(&name, USER)) }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/schemas/:full_name",
+            get(|Path(full_name): Path<String>| async move {
+                let (catalog, schema) = full_name.split_once('.').unwrap();
+                Json(common::schema_json(catalog, schema, USER))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/tables/:full_name",
+            get(|Path(full_name): Path<String>| async move {
+                let parts: Vec<&str> = full_name.split('.').collect();
+                let mut table: Value = common::table_json(parts[0], parts[1], parts[2], USER);
+                table["storage_location"] = json!(location);
+                Json(table)
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/effective-permissions/:securable_type/:full_name",
+            get(|| async { Json(json!({ "privilege_assignments": [] })) }),
+        );
+    let base_url: String = common::spawn_stub(app).await;
+
+    let path: PathBuf = log_path("writer");
+    let sink: Arc<JsonLinesAuditSink> = Arc::new(JsonLinesAuditSink::open(&path).unwrap());
+    let writer: DeltaLakeManager = DeltaLakeManager::builder()
+        .principal(USER)
+        .api_client(common::api_client(&base_url))
+        .storage_option("azure_storage_sas_key", "sv=2022-11-02")
+        .audit_sink(sink.clone())
+        .build()
+        .await
+        .unwrap();
+
+    let df: DataFrame = SessionContext::new().read_batch(batch).unwrap();
+    writer
+        .write_datafusion_to_delta("main.sales.orders", df)
+        .await
+        .unwrap();
+
+    let table: DeltaTable = deltalake::open_table(&table_path).await.unwrap();
+    assert_eq!(table.version(), 1);
+    let records: Vec<AuditRecord> = read_records(&path);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].action, AuditAction::Write);
+    assert_eq!(records[0].table, "main.sales.orders");
+    assert!(records[0].allowed);
+    assert_eq!(records[0].table_version, Some(1));
+    // the size of the parquet file added by the commit
+    assert_eq!(records[0].bytes, Some(parquet_bytes() - existing));
+    assert!(records[0].bytes > Some(0));
+    std::fs::remove_dir_all(&table_dir).unwrap();
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use axum::{
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Form, Json, Router,
+};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::api::auth::{
+    AzureServicePrincipalCredentials, CredentialProvider, OAuthM2MCredentials, PatCredentials,
+};
+use databricks_rust_catalog::Error;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Stub for a workspace token endpoint plus an API route that echoes the bearer token it received.
+/// Tokens are numbered so tests can tell a cached token from a refreshed one.
+fn oauth_stub(token_requests: Arc<AtomicUsize>, expires_in: u64) -> Router {
+    Router::new()
+        .route(
+            "/oidc/v1/token",
+            post(
+                move |headers: HeaderMap, Form(form): Form<HashMap<String, String>>| async move {
+                    // "client-id:client-secret" base64 encoded
+                    if headers["authorization"] != "Basic Y2xpZW50LWlkOmNsaWVudC1zZWNyZXQ=" {
+                        return (
+                            StatusCode::UNAUTHORIZED,
+                            Json(json!({"error": "invalid_client"})),
+                        )
+                            .into_response();
+                    }
+                    assert_eq!(form["grant_type"], "client_credentials");
+                    assert_eq!(form["scope"], "all-apis");
+                    let n: usize = token_requests.fetch_add(1, Ordering::SeqCst) + 1;
+                    Json(json!({
+                        "access_token": format!("oauth-token-{}", n),
+                        "token_type": "Bearer",
+                        "expires_in": expires_in
+                    }))
+                    .into_response()
+                },
+            ),
+        )
+        .route(
+            "/api/2.0/preview/scim/v2/Me",
+            get(|headers: HeaderMap| async move {
+                let token: String = headers["authorization"].to_str().unwrap().to_string();
+                Json(json!({ "token": token }))
+            }),
+        )
+}
+
+#[tokio::test]
+async fn test_pat_credentials() {
+    let credentials: PatCredentials = PatCredentials::new("dapi123".to_string());
+    assert_eq!(credentials.token().await.unwrap(), "dapi123");
+    assert_eq!(credentials.auth_type(), "pat");
+}
+
+#[tokio::test]
+async fn test_oauth_m2m_caches_token() {
+    let token_requests: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let base_url: String = common::spawn_stub(oauth_stub(token_requests.clone(), 3600)).await;
+
+    let credentials: OAuthM2MCredentials = OAuthM2MCredentials::new(
+        &base_url,
+        "client-id".to_string(),
+        "client-secret".to_string(),
+    );
+    let client: APIClient =
+        APIClient::builder_with_credentials("localhost".to_string(), credentials)
+            .base_url(&base_url)
+            .build()
+            .unwrap();
+
+    for _ in 0..3 {
+        let response: Value = client.get("/api/2.0/preview/scim/v2/Me").await.unwrap();
+        assert_eq!(response["token"], "Bearer oauth-token-1");
+    }
+    assert_eq!(token_requests.load(Ordering::SeqCst), 1);
+    assert_eq!(client.credentials.auth_type(), "oauth-m2m");
+}
+
+#[tokio::test]
+async fn test_token_requests_use_the_client_http_settings() {
+    let router: Router = Router::new().route(
+        "/oidc/v1/token",
+        post(|headers: HeaderMap| async move {
+            assert_eq!(headers["user-agent"], "corp-agent/1.0");
+            Json(json!({"access_token": "oauth-token-1", "expires_in": 3600}))
+        }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+
+    let credentials: Arc<OAuthM2MCredentials> = Arc::new(OAuthM2MCredentials::new(
+        &base_url,
+        "client-id".to_string(),
+        "client-secret".to_string(),
+    ));
+    let _client: APIClient =
+        APIClient::builder_with_credentials("localhost".to_string(), credentials.clone())
+            .user_agent("corp-agent/1.0")
+            .build()
+            .unwrap();
+
+    assert_eq!(credentials.token().await.unwrap(), "oauth-token-1");
+}
+
+#[tokio::test]
+async fn test_oauth_m2m_refreshes_expiring_token() {
+    let token_requests: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    // tokens expiring within the refresh leeway are never reused
+    let base_url: String = common::spawn_stub(oauth_stub(token_requests.clone(), 30)).await;
+
+    let client: APIClient = APIClient::builder_with_credentials(
+        "localhost".to_string(),
+        OAuthM2MCredentials::new(
+            &base_url,
+            "client-id".to_string(),
+            "client-secret".to_string(),
+        ),
+    )
+    .base_url(&base_url)
+    .build()
+    .unwrap();
+
+    let first: Value = client.get("/api/2.0/preview/scim/v2/Me").await.unwrap();
+    let second: Value = client.get("/api/2.0/preview/scim/v2/Me").await.unwrap();
+    assert_eq!(first["token"], "Bearer oauth-token-1");
+    assert_eq!(second["token"], "Bearer oauth-token-2");
+    assert_eq!(token_requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_oauth_m2m_rejected_client_returns_authentication_failed() {
+    let token_requests: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let base_url: String = common::spawn_stub(oauth_stub(token_requests, 3600)).await;
+
+    let credentials: OAuthM2MCredentials = OAuthM2MCredentials::new(
+        &base_url,
+        "client-id".to_string(),
+        "wrong-secret".to_string(),
+    );
+    match credentials.token().await {
+        Err(Error::AuthenticationFailed(msg)) => assert!(msg.contains("invalid_client")),
+        other => panic!("expected AuthenticationFailed, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_azure_service_principal_token() {
+    let router: Router = Router::new().route(
+        "/:tenant_id/oauth2/v2.0/token",
+        post(
+            |Path(tenant_id): Path<String>, Form(form): Form<HashMap<String, String>>| async move {
+                assert_eq!(tenant_id, "tenant-1");
+                assert_eq!(form["grant_type"], "client_credentials");
+                assert_eq!(form["client_id"], "app-id");
+                assert_eq!(form["client_secret"], "app-secret");
+                assert_eq!(
+                    form["scope"],
+                    "2ff814a6-3304-4ab8-85cb-cd0e6f879c1d/.default"
+                );
+                Json(json!({
+                    "token_type": "Bearer",
+                    "expires_in": 3599,
+                    "access_token": "aad-token"
+                }))
+            },
+        ),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+
+    let credentials: AzureServicePrincipalCredentials = AzureServicePrincipalCredentials::new(
+        "tenant-1".to_string(),
+        "app-id".to_string(),
+        "app-secret".to_string(),
+    )
+    .with_authority_host(&base_url);
+    assert_eq!(credentials.token().await.unwrap(), "aad-token");
+    assert_eq!(credentials.auth_type(), "azure-client-secret");
+}
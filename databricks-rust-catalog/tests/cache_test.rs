@@ -0,0 +1,162 @@
+mod common;
+
+use axum::{http::Uri, Json, Router};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::api::cache::PermissionCache;
+use databricks_rust_catalog::api::evaluator::Decision;
+use databricks_rust_catalog::api::permissions::{Privilege, SecurableType};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const ANALYST: &str = "analyst@example.com";
+
+/// Stub answering every request needed to evaluate a decision and counting them.
+/// Every object is owned by `admins`, the analyst holds USE_CATALOG, USE_SCHEMA and SELECT.
+fn counting_stub(requests: Arc<AtomicUsize>) -> Router {
+    Router::new().fallback(move |uri: Uri| {
+        let requests: Arc<AtomicUsize> = requests.clone();
+        async move {
+            requests.fetch_add(1, Ordering::SeqCst);
+            let path: &str = uri.path();
+            let body: Value = if path.ends_with("/scim/v2/Me") {
+                common::user_json("token-user@example.com")
+            } else if path.ends_with("/scim/v2/Users") {
+                json!({ "Resources": [common::user_json(ANALYST)] })
+            } else if path.contains("/effective-permissions/") {
+                json!({ "privilege_assignments": [{"principal": ANALYST, "privileges": [
+                    {"privilege": "USE_CATALOG"}, {"privilege": "USE_SCHEMA"}, {"privilege": "SELECT"}
+                ]}]})
+            } else {
+                let full_name: &str = path.rsplit('/').next().unwrap();
+                json!({ "full_name": full_name, "owner": "admins" })
+            };
+            Json(body)
+        }
+    })
+}
+
+async fn setup() -> (APIClient, Arc<AtomicUsize>) {
+    let requests: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let base_url: String = common::spawn_stub(counting_stub(requests.clone())).await;
+    (common::api_client(&base_url), requests)
+}
+
+async fn check(
+    cache: &PermissionCache,
+    api_client: &APIClient,
+    privilege: Privilege,
+    full_name: &str,
+) -> bool {
+    cache
+        .check(
+            api_client.clone(),
+            ANALYST,
+            privilege,
+            SecurableType::Table,
+            full_name,
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_cached_decisions_skip_requests() {
+    let (api_client, requests) = setup().await;
+    let cache: PermissionCache = PermissionCache::new(Duration::from_secs(60));
+
+    assert!(check(&cache, &api_client, Privilege::Select, "main.sales.orders").await);
+    let first: usize = requests.load(Ordering::SeqCst);
+    assert!(first > 0);
+
+    // names are case insensitive
+    assert!(check(&cache, &api_client, Privilege::Select, "MAIN.sales.Orders").await);
+    assert_eq!(requests.load(Ordering::SeqCst), first);
+    assert!(cache
+        .check(
+            api_client.clone(),
+            "Analyst@Example.com",
+            Privilege::Select,
+            SecurableType::Table,
+            "main.sales.orders",
+        )
+        .await
+        .unwrap());
+    assert_eq!(requests.load(Ordering::SeqCst), first);
+
+    // denied decisions are cached as well
+    assert!(!check(&cache, &api_client, Privilege::Modify, "main.sales.orders").await);
+    assert_eq!(requests.load(Ordering::SeqCst), 2 * first);
+    assert!(!check(&cache, &api_client, Privilege::Modify, "main.sales.orders").await);
+    assert_eq!(requests.load(Ordering::SeqCst), 2 * first);
+    assert_eq!(cache.len(), 2);
+
+    let decision: Decision = cache
+        .evaluate(
+            api_client,
+            ANALYST,
+            Privilege::Select,
+            SecurableType::Table,
+            "main.sales.orders",
+        )
+        .await
+        .unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.checks.len(), 3);
+}
+
+#[tokio::test]
+async fn test_decisions_expire() {
+    let (api_client, requests) = setup().await;
+    let cache: PermissionCache = PermissionCache::new(Duration::from_millis(50));
+
+    check(&cache, &api_client, Privilege::Select, "main.sales.orders").await;
+    let first: usize = requests.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(cache.is_empty());
+
+    check(&cache, &api_client, Privilege::Select, "main.sales.orders").await;
+    assert_eq!(requests.load(Ordering::SeqCst), 2 * first);
+
+    // a TTL of zero disables caching
+    let disabled: PermissionCache = PermissionCache::new(Duration::ZERO);
+    check(
+        &disabled,
+        &api_client,
+        Privilege::Select,
+        "main.sales.orders",
+    )
+    .await;
+    assert!(disabled.is_empty());
+}
+
+#[tokio::test]
+async fn test_invalidation() {
+    let (api_client, _) = setup().await;
+    let cache: PermissionCache = PermissionCache::default();
+    for table in [
+        "main.sales.orders",
+        "main.sales.customers",
+        "main.hr.salaries",
+    ] {
+        check(&cache, &api_client, Privilege::Select, table).await;
+    }
+    assert_eq!(cache.len(), 3);
+
+    // invalidating a schema removes the decisions on its tables
+    cache.invalidate("main.sales");
+    assert_eq!(cache.len(), 1);
+    // `main.h` is a prefix of `main.hr` but not its parent
+    cache.invalidate("main.h");
+    assert_eq!(cache.len(), 1);
+
+    cache.invalidate_principal("someone.else@example.com");
+    assert_eq!(cache.len(), 1);
+    cache.invalidate_principal("ANALYST@example.com");
+    assert!(cache.is_empty());
+
+    check(&cache, &api_client, Privilege::Select, "main.sales.orders").await;
+    cache.clear();
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,77 @@
+#![allow(dead_code)]
+use axum::Router;
+use databricks_rust_catalog::api::api_client::APIClient;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+/// Serves the provided router on a random local port and returns its base URL i.e. `http://127.0.0.1:12345`.
+pub async fn spawn_stub(router: Router) -> String {
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+/// Builds an API client that sends every request to the stub at `base_url`.
+pub fn api_client(base_url: &str) -> APIClient {
+    APIClient::builder("localhost".to_string(), "token".to_string())
+        .base_url(base_url)
+        .build()
+        .unwrap()
+}
+
+/// SCIM `/Me` payload for a workspace user.
+pub fn user_json(user_name: &str) -> Value {
+    json!({
+        "id": "1234567890",
+        "userName": user_name,
+        "displayName": user_name,
+        "active": true
+    })
+}
+
+/// Unity Catalog catalog payload.
+pub fn catalog_json(name: &str, owner: &str) -> Value {
+    json!({
+        "name": name,
+        "owner": owner,
+        "metastore_id": "metastore-1",
+        "created_at": 1700000000000i64,
+        "created_by": owner,
+        "catalog_type": "MANAGED_CATALOG",
+        "full_name": name
+    })
+}
+
+/// Unity Catalog schema payload.
+pub fn schema_json(catalog_name: &str, name: &str, owner: &str) -> Value {
+    json!({
+        "name": name,
+        "catalog_name": catalog_name,
+        "owner": owner,
+        "metastore_id": "metastore-1",
+        "full_name": format!("{}.{}", catalog_name, name),
+        "created_at": 1700000000000i64,
+        "created_by": owner,
+        "schema_id": format!("{}-{}", catalog_name, name)
+    })
+}
+
+/// Unity Catalog table payload.
+pub fn table_json(catalog_name: &str, schema_name: &str, name: &str, owner: &str) -> Value {
+    json!({
+        "name": name,
+        "catalog_name": catalog_name,
+        "schema_name": schema_name,
+        "table_type": "MANAGED",
+        "data_source_format": "DELTA",
+        "storage_location": format!("abfss://data@account.dfs.core.windows.net/{}/{}/{}", catalog_name, schema_name, name),
+        "owner": owner,
+        "full_name": format!("{}.{}.{}", catalog_name, schema_name, name),
+        "created_at": 1700000000000i64,
+        "created_by": owner,
+        "table_id": format!("{}-{}-{}", catalog_name, schema_name, name)
+    })
+}
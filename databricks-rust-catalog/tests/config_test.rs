@@ -0,0 +1,172 @@
+mod common;
+
+use axum::{http::HeaderMap, routing::get, Json, Router};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::config::{Config, ConfigLoader};
+use databricks_rust_catalog::Error;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const DATABRICKSCFG: &str = "
+; developer profiles
+[DEFAULT]
+host = https://adb-111.11.azuredatabricks.net
+token = dapi-default
+
+[ci]
+host  = https://adb-222.22.azuredatabricks.net/
+client_id = sp-client
+client_secret = sp-secret
+user_name = ci-runner@example.com
+
+# azure service principal
+[azure]
+host = adb-333.33.azuredatabricks.net
+AUTH_TYPE = azure-client-secret
+azure_tenant_id = tenant-1
+azure_client_id = app-id
+azure_client_secret = app-secret
+";
+
+/// Writes a configuration file to a unique temporary path.
+fn write_config(name: &str, contents: &str) -> PathBuf {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("databrickscfg-{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+    vars.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_load_default_and_named_profiles() {
+    let path: PathBuf = write_config("profiles", DATABRICKSCFG);
+
+    let default: Config = ConfigLoader::new()
+        .config_file(&path)
+        .env(env(&[]))
+        .load()
+        .unwrap();
+    assert_eq!(default.profile.as_deref(), Some("DEFAULT"));
+    assert_eq!(
+        default.workspace_name().unwrap(),
+        "adb-111.11.azuredatabricks.net"
+    );
+    assert_eq!(default.resolved_auth_type().unwrap(), "pat");
+
+    let ci: Config = ConfigLoader::new()
+        .config_file(&path)
+        .env(env(&[("DATABRICKS_CONFIG_PROFILE", "ci")]))
+        .load()
+        .unwrap();
+    assert_eq!(
+        ci.base_url().unwrap(),
+        "https://adb-222.22.azuredatabricks.net"
+    );
+    assert_eq!(ci.resolved_auth_type().unwrap(), "oauth-m2m");
+    assert_eq!(ci.user_name().unwrap(), "ci-runner@example.com");
+    assert_eq!(ci.credentials().unwrap().auth_type(), "oauth-m2m");
+
+    let azure: Config = ConfigLoader::new()
+        .profile("azure")
+        .config_file(&path)
+        .env(env(&[]))
+        .load()
+        .unwrap();
+    assert_eq!(
+        azure.base_url().unwrap(),
+        "https://adb-333.33.azuredatabricks.net"
+    );
+    assert_eq!(
+        azure.credentials().unwrap().auth_type(),
+        "azure-client-secret"
+    );
+    // secrets never show up in debug output
+    assert!(!format!("{:?}", azure).contains("app-secret"));
+}
+
+#[test]
+fn test_env_overrides_profile() {
+    let path: PathBuf = write_config("overrides", DATABRICKSCFG);
+
+    let config: Config = ConfigLoader::new()
+        .config_file(&path)
+        .env(env(&[
+            ("DATABRICKS_HOST", "https://adb-999.99.azuredatabricks.net"),
+            ("WORKSPACE_NAME", "ignored.azuredatabricks.net"),
+            ("DB_TOKEN", "dapi-env"),
+            ("USER_NAME", "first.last@example.com"),
+            ("STORAGE_URL", "http://localhost"),
+            ("STORAGE_PORT", "8000"),
+        ]))
+        .load()
+        .unwrap();
+    assert_eq!(
+        config.workspace_name().unwrap(),
+        "adb-999.99.azuredatabricks.net"
+    );
+    assert_eq!(config.token.as_deref(), Some("dapi-env"));
+    assert_eq!(config.user_name().unwrap(), "first.last@example.com");
+    assert_eq!(config.storage_endpoint().unwrap(), "http://localhost:8000");
+}
+
+#[test]
+fn test_missing_settings_return_config_errors() {
+    let path: PathBuf = write_config("missing", DATABRICKSCFG);
+
+    match ConfigLoader::new()
+        .profile("prod")
+        .config_file(&path)
+        .env(env(&[]))
+        .load()
+    {
+        Err(Error::Config(msg)) => assert!(msg.contains("profile prod not found")),
+        other => panic!("expected Config error, got {:?}", other),
+    }
+
+    // no file and no environment: loading succeeds, using the settings fails
+    let empty: Config = ConfigLoader::new()
+        .config_file(std::env::temp_dir().join("does-not-exist.databrickscfg"))
+        .env(env(&[]))
+        .load()
+        .unwrap();
+    assert!(matches!(empty.workspace_name(), Err(Error::Config(_))));
+    assert!(matches!(empty.credentials(), Err(Error::Config(_))));
+    assert!(matches!(empty.storage_endpoint(), Err(Error::Config(_))));
+
+    let broken: PathBuf = write_config("broken", "host = https://adb-111.11.azuredatabricks.net\n");
+    assert!(matches!(
+        ConfigLoader::new()
+            .config_file(&broken)
+            .env(env(&[]))
+            .load(),
+        Err(Error::Config(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_api_client_from_config() {
+    let router: Router = Router::new().route(
+        "/api/2.0/preview/scim/v2/Me",
+        get(|headers: HeaderMap| async move {
+            let token: String = headers["authorization"].to_str().unwrap().to_string();
+            Json(json!({ "token": token }))
+        }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+
+    let config: Config = ConfigLoader::new()
+        .config_file(write_config("client", DATABRICKSCFG))
+        .env(env(&[("DATABRICKS_HOST", &base_url)]))
+        .load()
+        .unwrap();
+    let client: APIClient = APIClient::from_config(&config).unwrap();
+
+    let response: Value = client.get("/api/2.0/preview/scim/v2/Me").await.unwrap();
+    assert_eq!(response["token"], "Bearer dapi-default");
+}
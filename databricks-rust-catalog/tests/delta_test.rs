@@ -0,0 +1,125 @@
+mod common;
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use databricks_rust_catalog::api::auth::PatCredentials;
+use databricks_rust_catalog::api::delta::DeltaLakeManager;
+use databricks_rust_catalog::Error;
+use magic_crypt::{new_magic_crypt, MagicCryptTrait};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Stub for the workspace `/Me` endpoint and the storage credential service.
+/// The credential service encrypts its response with its bearer token, like databricks-rust-service.
+fn workspace_stub() -> Router {
+    Router::new()
+        .route(
+            "/api/2.0/preview/scim/v2/Me",
+            get(|| async { Json(common::user_json("first.last@example.com")) }),
+        )
+        .route(
+            "/api/storage_credentials",
+            get(|headers: HeaderMap| async move {
+                if headers["authorization"] != "Bearer service-token" {
+                    return (
+                        StatusCode::UNAUTHORIZED,
+                        Json(json!({"error": "Unauthorized"})),
+                    )
+                        .into_response();
+                }
+                let mc = new_magic_crypt!("service-token", 256);
+                Json(json!({
+                    "azure_storage_account_name": mc.encrypt_str_to_base64("account"),
+                    "azure_client_id": mc.encrypt_str_to_base64("client-id"),
+                    "azure_client_secret": mc.encrypt_str_to_base64("client-secret"),
+                    "azure_tenant_id": mc.encrypt_str_to_base64("tenant-id"),
+                }))
+                .into_response()
+            }),
+        )
+}
+
+#[tokio::test]
+async fn test_builder_fetches_storage_credentials() {
+    let base_url: String = common::spawn_stub(workspace_stub()).await;
+
+    let reader: DeltaLakeManager = DeltaLakeManager::builder()
+        .principal("first.last@example.com")
+        .workspace("localhost")
+        .base_url(&base_url)
+        .credentials(PatCredentials::new("token".to_string()))
+        .credential_service(&base_url, "service-token")
+        .storage_option("azure_storage_account_name", "override")
+        .max_concurrent_reads(2)
+        .build()
+        .await
+        .unwrap();
+
+    let options: &HashMap<String, String> = reader.storage_options();
+    assert_eq!(options["azure_client_id"], "client-id");
+    assert_eq!(options["azure_tenant_id"], "tenant-id");
+    // explicit options win over the credential service
+    assert_eq!(options["azure_storage_account_name"], "override");
+    assert_eq!(reader.principal(), "first.last@example.com");
+}
+
+#[tokio::test]
+async fn test_builder_with_storage_options_only() {
+    let base_url: String = common::spawn_stub(workspace_stub()).await;
+
+    let storage_options: HashMap<String, String> = HashMap::from([(
+        "azure_storage_sas_key".to_string(),
+        "sv=2022-11-02".to_string(),
+    )]);
+    let reader: DeltaLakeManager = DeltaLakeManager::builder()
+        .principal("first.last@example.com")
+        .api_client(common::api_client(&base_url))
+        .storage_options(storage_options.clone())
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(reader.storage_options(), &storage_options);
+}
+
+#[tokio::test]
+async fn test_builder_reports_missing_configuration() {
+    let base_url: String = common::spawn_stub(workspace_stub()).await;
+
+    let missing_principal = DeltaLakeManager::builder()
+        .api_client(common::api_client(&base_url))
+        .storage_option("azure_storage_sas_key", "sv=2022-11-02")
+        .build()
+        .await;
+    assert!(matches!(missing_principal, Err(Error::Config(_))));
+
+    let missing_storage = DeltaLakeManager::builder()
+        .principal("first.last@example.com")
+        .api_client(common::api_client(&base_url))
+        .build()
+        .await;
+    assert!(matches!(missing_storage, Err(Error::Config(_))));
+
+    let missing_workspace = DeltaLakeManager::builder()
+        .principal("first.last@example.com")
+        .credentials(PatCredentials::new("token".to_string()))
+        .storage_option("azure_storage_sas_key", "sv=2022-11-02")
+        .build()
+        .await;
+    assert!(matches!(missing_workspace, Err(Error::Config(_))));
+
+    let wrong_service_token = DeltaLakeManager::builder()
+        .principal("first.last@example.com")
+        .api_client(common::api_client(&base_url))
+        .credential_service(&base_url, "wrong-token")
+        .build()
+        .await;
+    match wrong_service_token {
+        Err(Error::Api(e)) => assert_eq!(e.status, 401),
+        other => panic!("expected Api error, got {:?}", other.err()),
+    }
+}
@@ -0,0 +1,324 @@
+use databricks_rust_catalog::api::evaluator::{
+    Decision, Operation, PrivilegeEvaluator, RequirementCheck, SecurableFacts,
+};
+use databricks_rust_catalog::api::permissions::{
+    EffectivePrivilege, EffectivePrivilegeAssignment, EffectivePrivileges, Identity, Privilege,
+    SecurableType,
+};
+
+const USER: &str = "first.last@example.com";
+
+fn identity(groups: &[&str]) -> Identity {
+    Identity {
+        user_name: USER.to_string(),
+        groups: groups.iter().map(|g| g.to_string()).collect(),
+    }
+}
+
+/// Builds one level of the hierarchy. `grants` holds (principal, privilege, inherited_from_name).
+fn level(
+    securable_type: SecurableType,
+    full_name: &str,
+    owner: &str,
+    grants: &[(&str, &str, Option<&str>)],
+) -> SecurableFacts {
+    let assignments: Vec<EffectivePrivilegeAssignment> = grants
+        .iter()
+        .map(
+            |(principal, privilege, inherited_from)| EffectivePrivilegeAssignment {
+                principal: principal.to_string(),
+                privileges: vec![EffectivePrivilege {
+                    privilege: privilege.to_string(),
+                    inherited_from_type: None,
+                    inherited_from_name: inherited_from.map(String::from),
+                }],
+            },
+        )
+        .collect();
+    SecurableFacts {
+        securable_type: securable_type.clone(),
+        full_name: full_name.to_string(),
+        owner: Some(owner.to_string()),
+        privileges: EffectivePrivileges {
+            securable_type,
+            full_name: full_name.to_string(),
+            assignments,
+        },
+    }
+}
+
+/// `main` -> `main.sales` -> `main.sales.orders`, all owned by `admins` unless overridden.
+fn hierarchy(
+    catalog: &[(&str, &str, Option<&str>)],
+    schema: &[(&str, &str, Option<&str>)],
+    table: &[(&str, &str, Option<&str>)],
+) -> Vec<SecurableFacts> {
+    vec![
+        level(SecurableType::Catalog, "main", "admins", catalog),
+        level(SecurableType::Schema, "main.sales", "admins", schema),
+        level(SecurableType::Table, "main.sales.orders", "admins", table),
+    ]
+}
+
+fn evaluate(identity: &Identity, levels: &[SecurableFacts], operation: Operation) -> Decision {
+    PrivilegeEvaluator::new(identity, levels).evaluate(operation)
+}
+
+fn missing(decision: &Decision) -> Vec<&str> {
+    decision
+        .checks
+        .iter()
+        .filter(|check| !check.is_satisfied())
+        .map(|check| check.privilege.as_str())
+        .collect()
+}
+
+#[test]
+fn test_read_requires_use_catalog_use_schema_and_select() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "USE_CATALOG", None)],
+        &[(USER, "USE_SCHEMA", None)],
+        &[(USER, "SELECT", None)],
+    );
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Read);
+    assert!(decision.allowed);
+    let privileges: Vec<&str> = decision
+        .checks
+        .iter()
+        .map(|c| c.privilege.as_str())
+        .collect();
+    assert_eq!(privileges, vec!["USE_CATALOG", "USE_SCHEMA", "SELECT"]);
+}
+
+#[test]
+fn test_select_without_use_catalog_is_denied() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[],
+        &[(USER, "USE_SCHEMA", None)],
+        &[(USER, "SELECT", None)],
+    );
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Read);
+    assert!(!decision.allowed);
+    assert_eq!(missing(&decision), vec!["USE_CATALOG"]);
+}
+
+#[test]
+fn test_select_without_use_schema_is_denied() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "USE_CATALOG", None)],
+        &[],
+        &[(USER, "SELECT", None)],
+    );
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Read);
+    assert!(!decision.allowed);
+    assert_eq!(missing(&decision), vec!["USE_SCHEMA"]);
+}
+
+#[test]
+fn test_select_inherited_from_catalog() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "USE_CATALOG", None), (USER, "SELECT", None)],
+        &[(USER, "USE_SCHEMA", None)],
+        &[(USER, "SELECT", Some("main"))],
+    );
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Read);
+    assert!(decision.allowed);
+    let select: &RequirementCheck = decision.checks.last().unwrap();
+    assert_eq!(
+        select.satisfied_by.as_deref(),
+        Some("SELECT granted to first.last@example.com on main")
+    );
+}
+
+#[test]
+fn test_all_privileges_satisfies_every_requirement() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "ALL_PRIVILEGES", None)],
+        &[(USER, "ALL_PRIVILEGES", Some("main"))],
+        &[(USER, "ALL_PRIVILEGES", Some("main"))],
+    );
+    assert!(evaluate(&identity(&[]), &levels, Operation::Read).allowed);
+    assert!(evaluate(&identity(&[]), &levels, Operation::Write).allowed);
+}
+
+#[test]
+fn test_table_owner_still_needs_use_privileges() {
+    let mut levels: Vec<SecurableFacts> = hierarchy(&[], &[], &[]);
+    levels[2].owner = Some(USER.to_string());
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Read);
+    assert!(!decision.allowed);
+    assert_eq!(missing(&decision), vec!["USE_CATALOG", "USE_SCHEMA"]);
+
+    let mut levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "USE_CATALOG", None)],
+        &[(USER, "USE_SCHEMA", None)],
+        &[],
+    );
+    levels[2].owner = Some(USER.to_string());
+    assert!(evaluate(&identity(&[]), &levels, Operation::Read).allowed);
+    assert!(evaluate(&identity(&[]), &levels, Operation::Write).allowed);
+}
+
+#[test]
+fn test_parent_ownership_is_not_inherited() {
+    // owning the catalog and schema satisfies the USE privileges, but not SELECT on the table
+    let mut levels: Vec<SecurableFacts> = hierarchy(&[], &[], &[]);
+    levels[0].owner = Some(USER.to_string());
+    levels[1].owner = Some(USER.to_string());
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Read);
+    assert!(!decision.allowed);
+    assert_eq!(missing(&decision), vec!["SELECT"]);
+}
+
+#[test]
+fn test_group_grants_and_group_ownership() {
+    let mut levels: Vec<SecurableFacts> = hierarchy(
+        &[("data-engineers", "USE_CATALOG", None)],
+        &[("account users", "USE_SCHEMA", None)],
+        &[],
+    );
+    levels[2].owner = Some("data-engineers".to_string());
+    let decision: Decision = evaluate(
+        &identity(&["data-engineers", "account users"]),
+        &levels,
+        Operation::Write,
+    );
+    assert!(decision.allowed);
+    assert_eq!(
+        decision.checks[2].satisfied_by.as_deref(),
+        Some("owner data-engineers of main.sales.orders")
+    );
+
+    // the same grants do not apply to principals outside the groups
+    assert!(!evaluate(&identity(&[]), &levels, Operation::Write).allowed);
+}
+
+#[test]
+fn test_write_requires_modify() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "USE_CATALOG", None)],
+        &[(USER, "USE_SCHEMA", None)],
+        &[(USER, "SELECT", None)],
+    );
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Write);
+    assert!(!decision.allowed);
+    assert_eq!(missing(&decision), vec!["MODIFY"]);
+
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "USE_CATALOG", None)],
+        &[(USER, "USE_SCHEMA", None)],
+        &[(USER, "MODIFY", None)],
+    );
+    assert!(evaluate(&identity(&[]), &levels, Operation::Write).allowed);
+    assert!(!evaluate(&identity(&[]), &levels, Operation::Read).allowed);
+}
+
+#[test]
+fn test_browse_without_use_privileges() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "BROWSE", None)],
+        &[(USER, "BROWSE", Some("main"))],
+        &[(USER, "BROWSE", Some("main"))],
+    );
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Browse);
+    assert!(decision.allowed);
+    assert_eq!(decision.checks.len(), 1);
+    // BROWSE does not allow reading data
+    assert!(!evaluate(&identity(&[]), &levels, Operation::Read).allowed);
+}
+
+#[test]
+fn test_browse_through_object_access() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "USE_CATALOG", None)],
+        &[(USER, "USE_SCHEMA", None)],
+        &[(USER, "MODIFY", None)],
+    );
+    assert!(evaluate(&identity(&[]), &levels, Operation::Browse).allowed);
+
+    let levels: Vec<SecurableFacts> = hierarchy(&[], &[], &[(USER, "SELECT", None)]);
+    let decision: Decision = evaluate(&identity(&[]), &levels, Operation::Browse);
+    assert!(!decision.allowed);
+    assert_eq!(
+        missing(&decision),
+        vec!["BROWSE", "USE_CATALOG", "USE_SCHEMA"]
+    );
+}
+
+#[test]
+fn test_schema_privileges_require_use_schema_on_the_schema() {
+    let levels: Vec<SecurableFacts> = hierarchy(
+        &[(USER, "USE_CATALOG", None)],
+        &[(USER, "CREATE_TABLE", None)],
+        &[],
+    )[..2]
+        .to_vec();
+    let decision: Decision = evaluate(
+        &identity(&[]),
+        &levels,
+        Operation::Privilege(Privilege::CreateTable),
+    );
+    assert!(!decision.allowed);
+    assert_eq!(missing(&decision), vec!["USE_SCHEMA"]);
+
+    // USE_SCHEMA itself is only checked once
+    let decision: Decision = evaluate(
+        &identity(&[]),
+        &levels,
+        Operation::Privilege(Privilege::UseSchema),
+    );
+    let privileges: Vec<&str> = decision
+        .checks
+        .iter()
+        .map(|c| c.privilege.as_str())
+        .collect();
+    assert_eq!(privileges, vec!["USE_CATALOG", "USE_SCHEMA"]);
+}
+
+#[test]
+fn test_privileges_outside_catalogs() {
+    let levels: Vec<SecurableFacts> = vec![level(
+        SecurableType::ExternalLocation,
+        "raw_landing",
+        "admins",
+        &[("data-platform", "READ_FILES", None)],
+    )];
+    let read_files: Decision = evaluate(
+        &identity(&["data-platform"]),
+        &levels,
+        Operation::Privilege(Privilege::ReadFiles),
+    );
+    assert!(read_files.allowed);
+    assert_eq!(read_files.checks.len(), 1);
+    assert!(
+        !evaluate(
+            &identity(&["data-platform"]),
+            &levels,
+            Operation::Privilege(Privilege::WriteFiles),
+        )
+        .allowed
+    );
+}
+
+#[test]
+fn test_privilege_names() {
+    assert_eq!(
+        "USE_CATALOG".parse::<Privilege>(),
+        Ok(Privilege::UseCatalog)
+    );
+    assert_eq!(
+        "use catalog".parse::<Privilege>(),
+        Ok(Privilege::UseCatalog)
+    );
+    assert_eq!(
+        " read_volume ".parse::<Privilege>(),
+        Ok(Privilege::ReadVolume)
+    );
+    assert_eq!("USAGE".parse::<Privilege>(), Err(()));
+    for privilege in Privilege::ALL {
+        assert_eq!(privilege.to_string().parse::<Privilege>(), Ok(privilege));
+    }
+    assert!(Privilege::Execute.is_granted_by("ALL_PRIVILEGES"));
+    assert!(Privilege::Execute.is_granted_by("execute"));
+    assert!(!Privilege::Execute.is_granted_by("SELECT"));
+}
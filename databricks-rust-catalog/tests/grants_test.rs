@@ -0,0 +1,266 @@
+mod common;
+
+use axum::{extract::Path, extract::State, routing::get, Json, Router};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::api::grants::{self, GrantsFile, ObjectPlan, Plan};
+use databricks_rust_catalog::api::permissions::{
+    PermissionsChange, Privilege, PrivilegeAssignmentsResponse, SecurableType,
+};
+use databricks_rust_catalog::Error;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+/// Direct grants per object, keyed by `{securable_type}/{full_name}`, then principal.
+type Grants = Arc<Mutex<BTreeMap<String, BTreeMap<String, BTreeSet<String>>>>>;
+
+const GRANTS_FILE: &str = r#"
+# managed by the data platform team
+[catalog.main]
+data-engineers = ["USE_CATALOG", "BROWSE"]
+
+[schema."main.sales"]
+data-engineers = ["use_schema"]   # names are case insensitive
+
+[table."main.sales.orders"]
+"analyst@example.com" = [
+    "SELECT",
+    "MODIFY", # temporary, see ticket
+]
+data-engineers = ["SELECT"]
+"#;
+
+/// Stub for the permissions endpoints, applying PATCH changes to the shared grants.
+fn permissions_stub(grants: Grants) -> Router {
+    Router::new()
+        .route(
+            "/api/2.1/unity-catalog/permissions/:securable_type/:full_name",
+            get(
+                |State(grants): State<Grants>,
+                 Path((securable_type, full_name)): Path<(String, String)>| async move {
+                    let grants = grants.lock().unwrap();
+                    Json(assignments(
+                        grants.get(&format!("{}/{}", securable_type, full_name)),
+                    ))
+                },
+            )
+            .patch(
+                |State(grants): State<Grants>,
+                 Path((securable_type, full_name)): Path<(String, String)>,
+                 Json(body): Json<Value>| async move {
+                    let mut grants = grants.lock().unwrap();
+                    let object = grants
+                        .entry(format!("{}/{}", securable_type, full_name))
+                        .or_default();
+                    for change in body["changes"].as_array().unwrap() {
+                        let held = object
+                            .entry(change["principal"].as_str().unwrap().to_string())
+                            .or_default();
+                        for privilege in change["add"].as_array().into_iter().flatten() {
+                            held.insert(privilege.as_str().unwrap().to_string());
+                        }
+                        for privilege in change["remove"].as_array().into_iter().flatten() {
+                            held.remove(privilege.as_str().unwrap());
+                        }
+                    }
+                    object.retain(|_, held| !held.is_empty());
+                    Json(assignments(Some(object)))
+                },
+            ),
+        )
+        .with_state(grants)
+}
+
+fn assignments(object: Option<&BTreeMap<String, BTreeSet<String>>>) -> Value {
+    let assignments: Vec<Value> = object
+        .into_iter()
+        .flatten()
+        .map(|(principal, privileges)| json!({"principal": principal, "privileges": privileges}))
+        .collect();
+    json!({ "privilege_assignments": assignments })
+}
+
+/// Current grants: `main` matches the file, `main.sales` has none, the table drifted.
+fn current_grants() -> Grants {
+    let object = |grants: &[(&str, &[&str])]| -> BTreeMap<String, BTreeSet<String>> {
+        grants
+            .iter()
+            .map(|(principal, privileges)| {
+                (
+                    principal.to_string(),
+                    privileges.iter().map(|p| p.to_string()).collect(),
+                )
+            })
+            .collect()
+    };
+    Arc::new(Mutex::new(BTreeMap::from([
+        (
+            "catalog/main".to_string(),
+            object(&[("data-engineers", &["USE_CATALOG", "BROWSE"])]),
+        ),
+        (
+            "table/main.sales.orders".to_string(),
+            object(&[
+                ("analyst@example.com", &["SELECT"]),
+                ("contractor@example.com", &["SELECT", "MODIFY"]),
+            ]),
+        ),
+    ])))
+}
+
+#[test]
+fn test_parse_grants_file() {
+    let file: GrantsFile = GrantsFile::from_toml(GRANTS_FILE).unwrap();
+    assert_eq!(file.objects.len(), 3);
+
+    assert_eq!(file.objects[0].securable_type, SecurableType::Catalog);
+    assert_eq!(file.objects[0].full_name, "main");
+    assert_eq!(
+        file.objects[1].principals["data-engineers"],
+        BTreeSet::from([Privilege::UseSchema])
+    );
+    let table = &file.objects[2];
+    assert_eq!(table.full_name, "main.sales.orders");
+    assert_eq!(
+        table.principals["analyst@example.com"],
+        BTreeSet::from([Privilege::Select, Privilege::Modify])
+    );
+}
+
+#[test]
+fn test_parse_errors_name_the_line() {
+    let cases: [(&str, usize, usize, &str); 6] = [
+        (
+            "[table.\"main.sales.orders\"]\nx = [\"READ\"]",
+            2,
+            5,
+            "unknown privilege READ",
+        ),
+        ("[view.main]", 1, 2, "unknown securable type view"),
+        ("x = [\"SELECT\"]", 1, 1, "unknown securable type x"),
+        (
+            "[catalog.main]\nfirst.last@example.com = [\"SELECT\"]",
+            2,
+            11,
+            "expected `.`, `=`",
+        ),
+        ("[catalog.main]\nx = [\"SELECT\",\n", 3, 1, "invalid array"),
+        (
+            "[catalog.main]\n\n[catalog.main]",
+            3,
+            1,
+            "invalid table header\nduplicate key `main`",
+        ),
+    ];
+    for (contents, expected_line, expected_column, expected) in cases {
+        match GrantsFile::from_toml(contents) {
+            Err(Error::InvalidGrants {
+                line,
+                column,
+                message,
+            }) => {
+                assert!(message.starts_with(expected), "{}", message);
+                assert_eq!(
+                    (line, column),
+                    (Some(expected_line), Some(expected_column)),
+                    "{}",
+                    message
+                );
+            }
+            other => panic!(
+                "expected an invalid grants error for {:?}, got {:?}",
+                contents, other
+            ),
+        }
+    }
+
+    assert!(matches!(
+        GrantsFile::load("does-not-exist.toml"),
+        Err(Error::Io { .. })
+    ));
+}
+
+#[test]
+fn test_diff_grants_and_revokes() {
+    let file: GrantsFile = GrantsFile::from_toml(GRANTS_FILE).unwrap();
+    let current: PrivilegeAssignmentsResponse = serde_json::from_value(json!({
+        "privilege_assignments": [
+            {"principal": "analyst@example.com", "privileges": ["SELECT"]},
+            {"principal": "contractor@example.com", "privileges": ["SELECT", "LEGACY_PRIVILEGE"]}
+        ]
+    }))
+    .unwrap();
+
+    let object: ObjectPlan = grants::diff(&file.objects[2], &current);
+    assert_eq!(
+        object.changes,
+        vec![
+            PermissionsChange::new("analyst@example.com").grant(&[Privilege::Modify]),
+            // unknown privileges are left untouched
+            PermissionsChange::new("contractor@example.com").revoke(&[Privilege::Select]),
+            PermissionsChange::new("data-engineers").grant(&[Privilege::Select]),
+        ]
+    );
+}
+
+#[test]
+fn test_diff_matches_principals_case_insensitively() {
+    let file: GrantsFile = GrantsFile::from_toml(GRANTS_FILE).unwrap();
+    let current: PrivilegeAssignmentsResponse = serde_json::from_value(json!({
+        "privilege_assignments": [
+            {"principal": "Analyst@Example.com", "privileges": ["SELECT"]},
+            {"principal": "Data-Engineers", "privileges": ["SELECT"]}
+        ]
+    }))
+    .unwrap();
+
+    // nothing is revoked from one spelling to be granted to the other
+    let object: ObjectPlan = grants::diff(&file.objects[2], &current);
+    assert_eq!(
+        object.changes,
+        vec![PermissionsChange::new("Analyst@Example.com").grant(&[Privilege::Modify])]
+    );
+}
+
+#[tokio::test]
+async fn test_plan_and_apply() {
+    let state: Grants = current_grants();
+    let base_url: String = common::spawn_stub(permissions_stub(state.clone())).await;
+    let api_client: APIClient = common::api_client(&base_url);
+    let file: GrantsFile = GrantsFile::from_toml(GRANTS_FILE).unwrap();
+
+    let plan: Plan = grants::plan(api_client.clone(), &file).await.unwrap();
+    // the catalog already matches the file
+    let objects: Vec<&str> = plan.objects.iter().map(|o| o.full_name.as_str()).collect();
+    assert_eq!(objects, vec!["main.sales", "main.sales.orders"]);
+    assert_eq!(plan.counts(), (3, 2));
+    assert_eq!(
+        plan.to_string(),
+        [
+            "schema main.sales",
+            "  + GRANT USE_SCHEMA TO data-engineers",
+            "table main.sales.orders",
+            "  + GRANT MODIFY TO analyst@example.com",
+            "  - REVOKE MODIFY, SELECT FROM contractor@example.com",
+            "  + GRANT SELECT TO data-engineers",
+            "Plan: 3 to grant, 2 to revoke on 2 objects.",
+            "",
+        ]
+        .join("\n")
+    );
+
+    let results: Vec<PrivilegeAssignmentsResponse> =
+        grants::apply(api_client.clone(), &plan).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(!state
+        .lock()
+        .unwrap()
+        .get("table/main.sales.orders")
+        .unwrap()
+        .contains_key("contractor@example.com"));
+
+    // applying converges, so the next plan is empty
+    let plan: Plan = grants::plan(api_client, &file).await.unwrap();
+    assert!(plan.is_empty());
+    assert_eq!(plan.to_string(), "No changes. Grants match the file.\n");
+}
@@ -0,0 +1,312 @@
+mod common;
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use databricks_rust_catalog::api::metastore::{Catalog, Client, ListOptions, Schema, Table};
+use databricks_rust_catalog::Error;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+async fn metastore_stub() -> Client {
+    let router: Router =
+        Router::new()
+            .route(
+                "/api/2.1/unity-catalog/catalogs/:name",
+                get(|Path(name): Path<String>| async move {
+                    Json(common::catalog_json(&name, "admins"))
+                }),
+            )
+            .route(
+                "/api/2.1/unity-catalog/schemas/:full_name",
+                get(|Path(full_name): Path<String>| async move {
+                    let (catalog, schema) = full_name.split_once('.').unwrap();
+                    Json(common::schema_json(catalog, schema, "admins"))
+                }),
+            )
+            .route(
+                "/api/2.1/unity-catalog/tables/:full_name",
+                get(|Path(full_name): Path<String>| async move {
+                    let parts: Vec<&str> = full_name.split('.').collect();
+                    if parts[2] == "missing" {
+                        return (
+                        StatusCode::NOT_FOUND,
+                        Json(json!({"error_code": "TABLE_DOES_NOT_EXIST", "message": "not found"})),
+                    )
+                        .into_response();
+                    }
+                    Json(common::table_json(
+                        parts[0],
+                        parts[1],
+                        parts[2],
+                        "first.last@example.com",
+                    ))
+                    .into_response()
+                }),
+            );
+    let base_url: String = common::spawn_stub(router).await;
+    Client::from_api_client(common::api_client(&base_url))
+}
+
+#[tokio::test]
+async fn test_get_catalog_schema_and_table() {
+    let client: Client = metastore_stub().await;
+
+    let catalog: Catalog = client.get_catalog("main".to_string()).await.unwrap();
+    let schema: Schema = client.get_schema("main.sales".to_string()).await.unwrap();
+    let table: Table = client.get_table("main.sales.orders").await.unwrap();
+
+    assert_eq!(catalog.full_name, "main");
+    assert_eq!(schema.full_name, "main.sales");
+    assert_eq!(table.full_name, "main.sales.orders");
+    assert_eq!(table.owner, "first.last@example.com");
+}
+
+#[tokio::test]
+async fn test_get_missing_table_returns_table_not_found() {
+    let client: Client = metastore_stub().await;
+
+    let result = client.get_table("main.sales.missing").await;
+
+    assert!(matches!(result, Err(Error::TableNotFound(ref name)) if name == "main.sales.missing"));
+}
+
+/// Serves list endpoints. Browse-only objects are only returned when `include_browse=true`.
+async fn listing_stub() -> Client {
+    let router: Router = Router::new()
+        .route(
+            "/api/2.1/unity-catalog/catalogs",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                let mut catalogs: Vec<Value> = vec![
+                    common::catalog_json("main", "admins"),
+                    common::catalog_json("sales_raw", "admins"),
+                ];
+                if params.get("include_browse").map(|v| v.as_str()) == Some("true") {
+                    let mut browse_only: Value = common::catalog_json("finance", "admins");
+                    browse_only["browse_only"] = json!(true);
+                    catalogs.push(browse_only);
+                }
+                Json(json!({ "catalogs": catalogs }))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/schemas",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                let catalog: String = params["catalog_name"].clone();
+                let schemas: Vec<Value> = ["sales_eu", "sales_us", "hr"]
+                    .iter()
+                    .map(|name| common::schema_json(&catalog, name, "admins"))
+                    .collect();
+                Json(json!({ "schemas": schemas }))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/tables",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                let (catalog, schema) = (
+                    params["catalog_name"].clone(),
+                    params["schema_name"].clone(),
+                );
+                let mut view: Value = common::table_json(&catalog, &schema, "orders_v", "admins");
+                view["table_type"] = json!("VIEW");
+                let tables: Vec<Value> = vec![
+                    common::table_json(&catalog, &schema, "orders", "admins"),
+                    common::table_json(&catalog, &schema, "customers", "admins"),
+                    view,
+                ];
+                Json(json!({ "tables": tables }))
+            }),
+        );
+    let base_url: String = common::spawn_stub(router).await;
+    Client::from_api_client(common::api_client(&base_url))
+}
+
+#[tokio::test]
+async fn test_list_catalogs_include_browse() {
+    let client: Client = listing_stub().await;
+
+    let catalogs: Vec<Catalog> = client.list_catalogs(&ListOptions::default()).await.unwrap();
+    let with_browse: Vec<Catalog> = client
+        .list_catalogs(&ListOptions::default().include_browse(true))
+        .await
+        .unwrap();
+
+    assert_eq!(catalogs.len(), 2);
+    assert_eq!(with_browse.len(), 3);
+    assert_eq!(with_browse[2].browse_only, Some(true));
+}
+
+#[tokio::test]
+async fn test_list_schemas_name_pattern() {
+    let client: Client = listing_stub().await;
+
+    let schemas: Vec<Schema> = client
+        .list_schemas("main", &ListOptions::default().name_pattern("SALES_*"))
+        .await
+        .unwrap();
+
+    let names: Vec<&str> = schemas.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["sales_eu", "sales_us"]);
+}
+
+#[tokio::test]
+async fn test_list_tables_filters() {
+    let client: Client = listing_stub().await;
+
+    let all: Vec<Table> = client
+        .list_tables("main", "sales", &ListOptions::default())
+        .await
+        .unwrap();
+    let views: Vec<Table> = client
+        .list_tables("main", "sales", &ListOptions::default().table_type("view"))
+        .await
+        .unwrap();
+    let orders: Vec<Table> = client
+        .list_tables(
+            "main",
+            "sales",
+            &ListOptions::default()
+                .name_pattern("ord?rs*")
+                .table_type("MANAGED"),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(all.len(), 3);
+    assert_eq!(views.len(), 1);
+    assert_eq!(views[0].full_name, "main.sales.orders_v");
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].full_name, "main.sales.orders");
+}
+
+#[tokio::test]
+async fn test_list_tables_encodes_names() {
+    let client: Client = listing_stub().await;
+
+    let tables: Vec<Table> = client
+        .list_tables(
+            "r&d",
+            "eu sales#1",
+            &ListOptions::default().table_type("view"),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].full_name, "r&d.eu sales#1.orders_v");
+}
+
+#[tokio::test]
+async fn test_get_table_with_nested_metadata() {
+    let router: Router = Router::new().route(
+        "/api/2.1/unity-catalog/tables/:full_name",
+        get(|| async {
+            let mut table: Value = common::table_json("main", "sales", "orders", "admins");
+            table["columns"] = json!([
+                {"name": "order_id", "type_text": "bigint", "type_json": "{\"name\":\"order_id\",\"type\":\"long\",\"nullable\":false,\"metadata\":{}}", "type_name": "LONG", "position": 0, "nullable": false},
+                {"name": "region", "type_text": "string", "type_name": "STRING", "position": 1, "nullable": true, "partition_index": 0, "comment": "sales region"},
+                {"name": "email", "type_text": "string", "type_name": "STRING", "position": 2, "nullable": true, "mask": {"function_name": "main.sec.mask_email", "using_column_names": []}}
+            ]);
+            table["properties"] = json!({"delta.minReaderVersion": "1"});
+            table["table_constraints"] = json!([
+                {"primary_key_constraint": {"name": "orders_pk", "child_columns": ["order_id"]}},
+                {"foreign_key_constraint": {"name": "orders_customer_fk", "child_columns": ["customer_id"], "parent_table": "main.sales.customers", "parent_columns": ["id"]}}
+            ]);
+            table["row_filter"] = json!({"function_name": "main.sec.region_filter", "input_column_names": ["region"]});
+            table["dependencies"] = json!({"dependencies": [{"table": {"table_full_name": "main.sales.raw_orders"}}]});
+            table["delta_runtime_properties_kvpairs"] = json!({"delta_runtime_properties": {"delta.enableDeletionVectors": "true"}});
+            table["effective_predictive_optimization_flag"] = json!({"value": "ENABLE", "inherited_from_type": "CATALOG", "inherited_from_name": "main"});
+            Json(table)
+        }),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+    let client: Client = Client::from_api_client(common::api_client(&base_url));
+
+    let table: Table = client.get_table("main.sales.orders").await.unwrap();
+
+    let order_id = table.column("ORDER_ID").unwrap();
+    assert_eq!(order_id.type_text.as_deref(), Some("bigint"));
+    assert_eq!(order_id.nullable, Some(false));
+    assert!(order_id.type_json.as_deref().unwrap().contains("\"long\""));
+    assert_eq!(
+        table
+            .partition_columns()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["region"]
+    );
+    assert_eq!(
+        table
+            .column("email")
+            .unwrap()
+            .mask
+            .as_ref()
+            .unwrap()
+            .function_name,
+        "main.sec.mask_email"
+    );
+    assert_eq!(
+        table.properties.as_ref().unwrap()["delta.minReaderVersion"],
+        "1"
+    );
+    let constraints = table.table_constraints.as_ref().unwrap();
+    assert_eq!(
+        constraints[0]
+            .primary_key_constraint
+            .as_ref()
+            .unwrap()
+            .child_columns,
+        vec!["order_id"]
+    );
+    assert_eq!(
+        constraints[1]
+            .foreign_key_constraint
+            .as_ref()
+            .unwrap()
+            .parent_table,
+        "main.sales.customers"
+    );
+    assert_eq!(
+        table
+            .row_filter
+            .as_ref()
+            .unwrap()
+            .input_column_names
+            .as_ref()
+            .unwrap(),
+        &vec!["region".to_string()]
+    );
+    let dependencies = table
+        .dependencies
+        .as_ref()
+        .unwrap()
+        .dependencies
+        .as_ref()
+        .unwrap();
+    assert_eq!(
+        dependencies[0].table.as_ref().unwrap().table_full_name,
+        "main.sales.raw_orders"
+    );
+    assert_eq!(
+        table
+            .delta_runtime_properties_kvpairs
+            .as_ref()
+            .unwrap()
+            .delta_runtime_properties["delta.enableDeletionVectors"],
+        "true"
+    );
+    assert_eq!(
+        table
+            .effective_predictive_optimization_flag
+            .as_ref()
+            .unwrap()
+            .inherited_from_name
+            .as_deref(),
+        Some("main")
+    );
+}
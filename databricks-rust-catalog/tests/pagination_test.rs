@@ -0,0 +1,82 @@
+mod common;
+
+use axum::{extract::Query, routing::get, Json, Router};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::api::metastore::{Table, TableResponse};
+use databricks_rust_catalog::Error;
+use futures::TryStreamExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Serves five tables in pages of two, handing out `next_page_token` until the last page.
+async fn paged_tables_stub() -> (APIClient, Arc<AtomicUsize>) {
+    let hits: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let router: Router = Router::new().route(
+        "/api/2.1/unity-catalog/tables",
+        get(
+            move |Query(params): Query<HashMap<String, String>>| async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let start: usize = params
+                    .get("page_token")
+                    .map(|token| token.trim_start_matches("offset=").parse().unwrap())
+                    .unwrap_or(0);
+                let end: usize = (start + 2).min(5);
+                let tables: Vec<Value> = (start..end)
+                    .map(|i| common::table_json("main", "sales", &format!("t{}", i), "owner"))
+                    .collect();
+                let mut body: Value = json!({ "tables": tables });
+                if end < 5 {
+                    body["next_page_token"] = json!(format!("offset={}", end));
+                }
+                Json(body)
+            },
+        ),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+    (common::api_client(&base_url), hits)
+}
+
+#[tokio::test]
+async fn test_paginate_follows_next_page_token() {
+    let (client, hits) = paged_tables_stub().await;
+
+    let tables: Vec<Table> = client
+        .paginate_all::<TableResponse>(
+            "/api/2.1/unity-catalog/tables?catalog_name=main&schema_name=sales",
+        )
+        .await
+        .unwrap();
+
+    let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["t0", "t1", "t2", "t3", "t4"]);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_paginate_is_lazy() {
+    let (client, hits) = paged_tables_stub().await;
+
+    let mut stream = client.paginate::<TableResponse>(
+        "/api/2.1/unity-catalog/tables?catalog_name=main&schema_name=sales",
+    );
+    let first: Table = stream.try_next().await.unwrap().unwrap();
+    let second: Table = stream.try_next().await.unwrap().unwrap();
+
+    assert_eq!(first.name, "t0");
+    assert_eq!(second.name, "t1");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_paginate_reports_invalid_base_url() {
+    let client: APIClient = common::api_client("http://[not-a-host");
+
+    let result = client
+        .paginate_all::<TableResponse>("/api/2.1/unity-catalog/tables")
+        .await;
+
+    assert!(matches!(result, Err(Error::InvalidUrl(_))));
+}
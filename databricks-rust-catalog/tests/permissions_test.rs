@@ -0,0 +1,713 @@
+mod common;
+
+use axum::{
+    extract::{Path, Query, Request},
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, patch},
+    Json, Router,
+};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::api::evaluator::{AccessExplanation, GrantTrace, LevelTrace};
+use databricks_rust_catalog::api::permissions::{
+    self, EffectivePrivilege, EffectivePrivileges, Identity, PermissionsChange, Privilege,
+    PrivilegeAssignmentsResponse, SecurableType, User,
+};
+use databricks_rust_catalog::Error;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Application id of a service principal in `data-engineers`.
+const ETL_APP: &str = "6f0a6c4e-5d6b-4b7e-9a35-0c1d2e3f4a5b";
+
+/// SCIM user with the given group ids as direct memberships.
+fn scim_user(user_name: &str, group_ids: &[&str]) -> Value {
+    let mut user: Value = common::user_json(user_name);
+    user["groups"] = group_ids
+        .iter()
+        .map(|id| json!({"value": id, "$ref": format!("Groups/{}", id), "type": "direct"}))
+        .collect();
+    user
+}
+
+/// Stub for SCIM users and groups, object ownership and effective permissions.
+/// Every object is owned by `admins`. `effective` maps full names to privilege assignments,
+/// which are filtered by the `principal` query parameter.
+///
+/// Group hierarchy: `member@example.com` and the service principal `ETL_APP` ->
+/// `data-engineers` -> `data-platform`, `admin@example.com` -> `admins`.
+fn permissions_stub(effective: Value) -> Router {
+    Router::new()
+        .route(
+            "/api/2.0/preview/scim/v2/Me",
+            get(|| async { Json(scim_user("token-user@example.com", &[])) }),
+        )
+        .route(
+            "/api/2.0/preview/scim/v2/Users",
+            get(|Query(query): Query<HashMap<String, String>>| async move {
+                // SCIM filters ignore case and answer with the stored user name
+                let user_name: String = query["filter"]
+                    .strip_prefix("userName eq \"")
+                    .and_then(|f| f.strip_suffix('"'))
+                    .unwrap()
+                    .to_lowercase();
+                let resources: Vec<Value> = match user_name.as_str() {
+                    "member@example.com" => vec![scim_user(&user_name, &["g1"])],
+                    "admin@example.com" => vec![scim_user(&user_name, &["g3"])],
+                    "nobody@example.com" | ETL_APP => vec![],
+                    _ => vec![scim_user(&user_name, &[])],
+                };
+                Json(json!({ "totalResults": resources.len(), "Resources": resources }))
+            }),
+        )
+        .route(
+            "/api/2.0/preview/scim/v2/ServicePrincipals",
+            get(|Query(query): Query<HashMap<String, String>>| async move {
+                let application_id: &str = query["filter"]
+                    .strip_prefix("applicationId eq \"")
+                    .and_then(|f| f.strip_suffix('"'))
+                    .unwrap();
+                let resources: Vec<Value> = match application_id {
+                    ETL_APP => vec![json!({
+                        "id": "4321",
+                        "applicationId": ETL_APP,
+                        "displayName": "nightly-etl",
+                        "active": true,
+                        "groups": [{"value": "g1", "type": "direct"}]
+                    })],
+                    _ => vec![],
+                };
+                Json(json!({ "totalResults": resources.len(), "Resources": resources }))
+            }),
+        )
+        .route(
+            "/api/2.0/preview/scim/v2/Groups/:id",
+            get(|Path(id): Path<String>| async move {
+                let (display_name, parents): (&str, Vec<Value>) = match id.as_str() {
+                    "g1" => ("data-engineers", vec![json!({"value": "g2", "display": "data-platform"})]),
+                    "g2" => ("data-platform", vec![]),
+                    _ => ("admins", vec![]),
+                };
+                Json(json!({ "id": id, "displayName": display_name, "groups": parents }))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/catalogs/:name",
+            get(|Path(name): Path<String>| async move {
+                Json(common::catalog_json(&name, "admins"))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/schemas/:full_name",
+            get(|Path(full_name): Path<String>| async move {
+                let (catalog, schema) = full_name.split_once('.').unwrap();
+                Json(common::schema_json(catalog, schema, "admins"))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/tables/:full_name",
+            get(|Path(full_name): Path<String>| async move {
+                let parts: Vec<&str> = full_name.split('.').collect();
+                Json(common::table_json(parts[0], parts[1], parts[2], "admins"))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/:securables/:full_name",
+            get(|Path((securables, full_name)): Path<(String, String)>| async move {
+                assert!(["volumes", "functions", "external_locations"].contains(&securables.as_str()));
+                Json(json!({ "full_name": full_name, "owner": "admins" }))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/effective-permissions/:securable_type/:full_name",
+            get(
+                move |Path((securable_type, full_name)): Path<(String, String)>,
+                      Query(query): Query<HashMap<String, String>>| {
+                    let effective: Value = effective.clone();
+                    async move {
+                        assert!([
+                            "catalog",
+                            "schema",
+                            "table",
+                            "volume",
+                            "function",
+                            "external_location"
+                        ]
+                        .contains(&securable_type.as_str()));
+                        let assignments: Vec<Value> = effective[&full_name]
+                            .as_array()
+                            .unwrap()
+                            .iter()
+                            .filter(|a| match query.get("principal") {
+                                Some(principal) => a["principal"] == principal.as_str(),
+                                None => true,
+                            })
+                            .cloned()
+                            .collect();
+                        Json(json!({ "privilege_assignments": assignments }))
+                    }
+                },
+            ),
+        )
+}
+
+/// Effective permissions on `main`, `main.sales` and `main.sales.orders`.
+fn effective_permissions() -> Value {
+    json!({
+        "main": [
+            {"principal": "analyst@example.com", "privileges": [{"privilege": "USE_CATALOG"}, {"privilege": "SELECT"}]},
+            {"principal": "engineer@example.com", "privileges": [{"privilege": "USE_CATALOG"}]},
+            {"principal": "data-engineers", "privileges": [{"privilege": "USE_CATALOG"}]}
+        ],
+        "main.sales": [
+            {"principal": "analyst@example.com", "privileges": [
+                {"privilege": "USE_SCHEMA"},
+                {"privilege": "SELECT", "inherited_from_type": "CATALOG", "inherited_from_name": "main"}
+            ]},
+            {"principal": "engineer@example.com", "privileges": [{"privilege": "USE_SCHEMA"}]},
+            {"principal": "data-platform", "privileges": [{"privilege": "USE_SCHEMA"}, {"privilege": "SELECT"}]}
+        ],
+        "main.sales.orders": [
+            {"principal": "analyst@example.com", "privileges": [
+                {"privilege": "SELECT", "inherited_from_type": "CATALOG", "inherited_from_name": "main"}
+            ]},
+            {"principal": "data-platform", "privileges": [
+                {"privilege": "SELECT", "inherited_from_type": "SCHEMA", "inherited_from_name": "main.sales"}
+            ]},
+            {"principal": "engineer@example.com", "privileges": [{"privilege": "MODIFY"}]}
+        ]
+    })
+}
+
+#[tokio::test]
+async fn test_get_effective_permissions() {
+    let base_url: String = common::spawn_stub(permissions_stub(effective_permissions())).await;
+    let api_client: APIClient = common::api_client(&base_url);
+
+    let privileges: EffectivePrivileges = permissions::get_effective_permissions(
+        api_client,
+        SecurableType::Table,
+        "main.sales.orders",
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(privileges.assignments.len(), 3);
+
+    let select: &EffectivePrivilege = privileges
+        .find("analyst@example.com", Privilege::Select)
+        .unwrap();
+    assert!(select.is_inherited());
+    assert_eq!(select.inherited_from_type, Some(SecurableType::Catalog));
+    assert_eq!(select.inherited_from_name.as_deref(), Some("main"));
+
+    let modify: &EffectivePrivilege = privileges
+        .find("engineer@example.com", Privilege::Modify)
+        .unwrap();
+    assert!(!modify.is_inherited());
+
+    assert!(privileges.has_any(
+        &["engineer@example.com"],
+        &[Privilege::Select, Privilege::Modify]
+    ));
+    assert!(!privileges.has_any(&["engineer@example.com"], &[Privilege::Select]));
+    assert_eq!(privileges.privileges_for("nobody@example.com").count(), 0);
+}
+
+#[tokio::test]
+async fn test_can_read_and_write_use_inherited_privileges() {
+    let base_url: String = common::spawn_stub(permissions_stub(effective_permissions())).await;
+    let api_client: APIClient = common::api_client(&base_url);
+
+    // SELECT granted on the catalog is inherited by the table
+    assert!(permissions::can_read(
+        api_client.clone(),
+        "main.sales.orders",
+        "analyst@example.com"
+    )
+    .await
+    .unwrap());
+    assert!(!permissions::can_write(
+        api_client.clone(),
+        "main.sales.orders",
+        "analyst@example.com"
+    )
+    .await
+    .unwrap());
+    assert!(permissions::can_write(
+        api_client.clone(),
+        "main.sales.orders",
+        "engineer@example.com"
+    )
+    .await
+    .unwrap());
+    assert!(
+        !permissions::can_read(api_client, "main.sales.orders", "nobody@example.com")
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_resolve_identity_follows_nested_groups() {
+    let base_url: String = common::spawn_stub(permissions_stub(effective_permissions())).await;
+    let api_client: APIClient = common::api_client(&base_url);
+
+    let identity: Identity =
+        permissions::resolve_identity(api_client.clone(), "member@example.com")
+            .await
+            .unwrap();
+    assert_eq!(
+        identity.groups,
+        vec!["data-engineers", "data-platform", "account users"]
+    );
+    assert!(identity.is("member@example.com"));
+    assert!(identity.is("data-platform"));
+    assert!(!identity.is("admins"));
+
+    // the SCIM user name is kept and names match regardless of case
+    let identity: Identity =
+        permissions::resolve_identity(api_client.clone(), "Member@Example.com")
+            .await
+            .unwrap();
+    assert_eq!(identity.user_name, "member@example.com");
+    assert!(identity.is("MEMBER@example.com"));
+    assert!(identity.is("Data-Platform"));
+
+    // service principals are not users and are looked up by application id
+    let etl: Identity = permissions::resolve_identity(api_client.clone(), ETL_APP)
+        .await
+        .unwrap();
+    assert_eq!(etl.user_name, ETL_APP);
+    assert_eq!(
+        etl.groups,
+        vec!["data-engineers", "data-platform", "account users"]
+    );
+
+    // unknown principals only belong to the implicit account users group
+    let nobody: Identity = permissions::resolve_identity(api_client, "nobody@example.com")
+        .await
+        .unwrap();
+    assert_eq!(nobody.names(), vec!["nobody@example.com", "account users"]);
+}
+
+#[tokio::test]
+async fn test_user_filter_escapes_quotes() {
+    let filters: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let recorded: Arc<Mutex<Vec<String>>> = filters.clone();
+    let app: Router = Router::new()
+        .route(
+            "/api/2.0/preview/scim/v2/Me",
+            get(|| async { Json(scim_user("token-user@example.com", &[])) }),
+        )
+        .route(
+            "/api/2.0/preview/scim/v2/:resources",
+            get(
+                move |Query(query): Query<HashMap<String, String>>| async move {
+                    recorded.lock().unwrap().push(query["filter"].clone());
+                    Json(json!({ "totalResults": 0, "Resources": [] }))
+                },
+            ),
+        );
+    let api_client: APIClient = common::api_client(&common::spawn_stub(app).await);
+
+    // the quote cannot end the string and add a clause matching the admin
+    let principal: &str = "x\\\" or userName eq \"admin@example.com";
+    let identity: Identity = permissions::resolve_identity(api_client, principal)
+        .await
+        .unwrap();
+    assert_eq!(identity.user_name, principal);
+    assert_eq!(
+        *filters.lock().unwrap(),
+        vec![
+            "userName eq \"x\\\\\\\" or userName eq \\\"admin@example.com\"",
+            "applicationId eq \"x\\\\\\\" or userName eq \\\"admin@example.com\""
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_group_grants_and_ownership_are_matched() {
+    let base_url: String = common::spawn_stub(permissions_stub(effective_permissions())).await;
+    let api_client: APIClient = common::api_client(&base_url);
+
+    // SELECT granted to data-platform, which contains data-engineers
+    assert!(permissions::can_read(
+        api_client.clone(),
+        "main.sales.orders",
+        "member@example.com"
+    )
+    .await
+    .unwrap());
+    assert!(!permissions::can_write(
+        api_client.clone(),
+        "main.sales.orders",
+        "member@example.com"
+    )
+    .await
+    .unwrap());
+    // every object is owned by the admins group
+    assert!(
+        permissions::can_write(api_client.clone(), "main.sales.orders", "admin@example.com")
+            .await
+            .unwrap()
+    );
+
+    // principals are matched regardless of case
+    assert!(permissions::can_read(
+        api_client.clone(),
+        "main.sales.orders",
+        "Member@Example.com"
+    )
+    .await
+    .unwrap());
+    assert!(permissions::can_read(
+        api_client.clone(),
+        "main.sales.orders",
+        "Analyst@Example.com"
+    )
+    .await
+    .unwrap());
+    assert!(
+        permissions::can_write(api_client, "main.sales.orders", "ADMIN@example.com")
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_check_privileges_on_other_securables() {
+    let mut effective: Value = effective_permissions();
+    effective["main.sales.landing"] = json!([
+        {"principal": "analyst@example.com", "privileges": [{"privilege": "READ_VOLUME"}]}
+    ]);
+    effective["main.sales.mask_ssn"] = json!([
+        {"principal": "engineer@example.com", "privileges": [
+            {"privilege": "ALL_PRIVILEGES", "inherited_from_type": "SCHEMA", "inherited_from_name": "main.sales"}
+        ]}
+    ]);
+    effective["raw_landing"] = json!([
+        {"principal": "data-platform", "privileges": [{"privilege": "READ_FILES"}]}
+    ]);
+    let base_url: String = common::spawn_stub(permissions_stub(effective)).await;
+    let api_client: APIClient = common::api_client(&base_url);
+
+    assert!(permissions::check(
+        api_client.clone(),
+        "analyst@example.com",
+        Privilege::ReadVolume,
+        SecurableType::Volume,
+        "main.sales.landing"
+    )
+    .await
+    .unwrap());
+    assert!(!permissions::check(
+        api_client.clone(),
+        "analyst@example.com",
+        Privilege::WriteVolume,
+        SecurableType::Volume,
+        "main.sales.landing"
+    )
+    .await
+    .unwrap());
+    // ALL_PRIVILEGES inherited from the schema includes EXECUTE
+    assert!(permissions::check(
+        api_client.clone(),
+        "engineer@example.com",
+        Privilege::Execute,
+        SecurableType::Function,
+        "main.sales.mask_ssn"
+    )
+    .await
+    .unwrap());
+    // external locations are not inside a catalog, so no USE_* privileges are needed
+    assert!(permissions::check(
+        api_client.clone(),
+        "member@example.com",
+        Privilege::ReadFiles,
+        SecurableType::ExternalLocation,
+        "raw_landing"
+    )
+    .await
+    .unwrap());
+    assert!(!permissions::check(
+        api_client,
+        "analyst@example.com",
+        Privilege::ReadFiles,
+        SecurableType::ExternalLocation,
+        "raw_landing"
+    )
+    .await
+    .unwrap());
+}
+
+#[tokio::test]
+async fn test_update_permissions() {
+    // applies the changes to `analyst@example.com` holding SELECT and returns the resulting grants
+    let stub: Router = Router::new().route(
+        "/api/2.1/unity-catalog/permissions/:securable_type/:full_name",
+        patch(
+            |Path((securable_type, full_name)): Path<(String, String)>,
+             Json(body): Json<Value>| async move {
+                assert_eq!(securable_type, "table");
+                assert_eq!(full_name, "main.sales.orders");
+                let mut grants: HashMap<String, BTreeSet<String>> = HashMap::from([(
+                    "analyst@example.com".to_string(),
+                    BTreeSet::from(["SELECT".to_string()]),
+                )]);
+                for change in body["changes"].as_array().unwrap() {
+                    let principal: String = change["principal"].as_str().unwrap().to_string();
+                    let held: &mut BTreeSet<String> = grants.entry(principal).or_default();
+                    for privilege in change["add"].as_array().into_iter().flatten() {
+                        held.insert(privilege.as_str().unwrap().to_string());
+                    }
+                    for privilege in change["remove"].as_array().into_iter().flatten() {
+                        held.remove(privilege.as_str().unwrap());
+                    }
+                }
+                let mut assignments: Vec<Value> = grants
+                    .into_iter()
+                    .filter(|(_, privileges)| !privileges.is_empty())
+                    .map(|(principal, privileges)| json!({"principal": principal, "privileges": privileges}))
+                    .collect();
+                assignments.sort_by_key(|a| a["principal"].as_str().unwrap().to_string());
+                Json(json!({ "privilege_assignments": assignments, "changes": body["changes"] }))
+            },
+        ),
+    );
+    let base_url: String = common::spawn_stub(stub).await;
+    let api_client: APIClient = common::api_client(&base_url);
+
+    let changes: Vec<PermissionsChange> = vec![
+        PermissionsChange::new("data-engineers").grant(&[Privilege::Select, Privilege::Modify]),
+        PermissionsChange::new("analyst@example.com").revoke(&[Privilege::Select]),
+    ];
+    let response: PrivilegeAssignmentsResponse = permissions::update_permissions(
+        api_client,
+        SecurableType::Table,
+        "main.sales.orders",
+        &changes,
+    )
+    .await
+    .unwrap();
+
+    let assignments = response.privilege_assignments.unwrap();
+    assert_eq!(assignments.len(), 1);
+    assert_eq!(assignments[0].principal.as_deref(), Some("data-engineers"));
+    assert_eq!(
+        assignments[0].privileges,
+        Some(vec!["MODIFY".to_string(), "SELECT".to_string()])
+    );
+    assert_eq!(assignments[0].object_name, "main.sales.orders");
+    assert_eq!(assignments[0].object_type, Some(SecurableType::Table));
+}
+
+#[test]
+fn test_permissions_change_serialization() {
+    let change: PermissionsChange = PermissionsChange::new("data-engineers")
+        .grant(&[Privilege::UseCatalog])
+        .grant(&[Privilege::Browse]);
+    // empty lists are left out of the request
+    assert_eq!(
+        serde_json::to_value(&change).unwrap(),
+        json!({"principal": "data-engineers", "add": ["USE_CATALOG", "BROWSE"]})
+    );
+}
+
+#[tokio::test]
+async fn test_lookups_run_concurrently() {
+    // every request is held for a while so requests that are sent concurrently overlap
+    const DELAY: Duration = Duration::from_millis(100);
+    let requests: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let in_flight: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let max_in_flight: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let counters: (Arc<AtomicUsize>, Arc<AtomicUsize>, Arc<AtomicUsize>) =
+        (requests.clone(), in_flight.clone(), max_in_flight.clone());
+    let stub: Router = permissions_stub(effective_permissions()).layer(middleware::from_fn(
+        move |request: Request, next: Next| {
+            let (requests, in_flight, max_in_flight) = counters.clone();
+            async move {
+                requests.fetch_add(1, Ordering::SeqCst);
+                let current: usize = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(DELAY).await;
+                let response: Response = next.run(request).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                response
+            }
+        },
+    ));
+    let base_url: String = common::spawn_stub(stub).await;
+    let api_client: APIClient = common::api_client(&base_url);
+
+    let allowed: bool =
+        permissions::can_read(api_client, "main.sales.orders", "member@example.com")
+            .await
+            .unwrap();
+    assert!(allowed);
+
+    // /Me, /Users and two /Groups lookups resolve the identity one after another, while the
+    // owner and effective permissions of the three levels are fetched alongside them
+    assert_eq!(requests.load(Ordering::SeqCst), 10);
+    let max_in_flight: usize = max_in_flight.load(Ordering::SeqCst);
+    assert!(
+        max_in_flight >= 2,
+        "at most {} request was in flight at a time",
+        max_in_flight
+    );
+}
+
+/// Stub answering `/Me` with the given status and body.
+async fn me_client(status: u16, body: Value) -> APIClient {
+    let app: Router = Router::new().route(
+        "/api/2.0/preview/scim/v2/Me",
+        get(move || async move {
+            (
+                axum::http::StatusCode::from_u16(status).unwrap(),
+                Json(body),
+            )
+        }),
+    );
+    common::api_client(&common::spawn_stub(app).await)
+}
+
+#[tokio::test]
+async fn test_authenticate_user_binds_the_token_identity() {
+    let api_client: APIClient = me_client(200, common::user_json("first.last@example.com")).await;
+
+    // user names are case insensitive
+    let user: User = permissions::authenticate_user(api_client.clone(), "First.Last@example.com")
+        .await
+        .unwrap();
+    assert_eq!(user.principal(), "first.last@example.com");
+    assert!(!user.is_service_principal());
+
+    // a valid token does not allow claiming to be somebody else
+    match permissions::authenticate_user(api_client, "admin@example.com").await {
+        Err(Error::IdentityMismatch { claimed, actual }) => {
+            assert_eq!(claimed, "admin@example.com");
+            assert_eq!(actual, "first.last@example.com");
+        }
+        other => panic!("expected an identity mismatch, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_authenticate_service_principal() {
+    let application_id: &str = "6f0a6c4e-5d6b-4b7e-9a35-0c1d2e3f4a5b";
+    let api_client: APIClient = me_client(
+        200,
+        json!({
+            "id": "4321",
+            "applicationId": application_id,
+            "displayName": "nightly-etl",
+            "active": true
+        }),
+    )
+    .await;
+
+    let user: User = permissions::authenticate_user(api_client.clone(), application_id)
+        .await
+        .unwrap();
+    assert!(user.is_service_principal());
+    assert_eq!(user.principal(), application_id);
+
+    // the display name is not an identity
+    assert!(matches!(
+        permissions::authenticate_user(api_client, "nightly-etl").await,
+        Err(Error::IdentityMismatch { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_authenticate_user_rejects_invalid_tokens_and_inactive_users() {
+    let rejected: APIClient = me_client(
+        401,
+        json!({"error_code": "UNAUTHENTICATED", "message": "Invalid access token."}),
+    )
+    .await;
+    assert!(matches!(
+        permissions::authenticate_user(rejected, "first.last@example.com").await,
+        Err(Error::AuthenticationFailed(_))
+    ));
+
+    let mut inactive: Value = common::user_json("first.last@example.com");
+    inactive["active"] = json!(false);
+    let api_client: APIClient = me_client(200, inactive).await;
+    assert!(matches!(
+        permissions::authenticate_user(api_client, "first.last@example.com").await,
+        Err(Error::AuthenticationFailed(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_explain_access() {
+    let base_url: String = common::spawn_stub(permissions_stub(effective_permissions())).await;
+    let api_client: APIClient = common::api_client(&base_url);
+
+    // allowed through grants held by the nested group data-platform
+    let explanation: AccessExplanation = permissions::explain_access(
+        api_client.clone(),
+        "member@example.com",
+        Privilege::Select,
+        SecurableType::Table,
+        "main.sales.orders",
+    )
+    .await
+    .unwrap();
+    assert!(explanation.allowed);
+    assert!(explanation.missing.is_empty());
+    assert_eq!(explanation.verdict, "allowed: SELECT on main.sales.orders");
+    let table: &LevelTrace = &explanation.levels[2];
+    assert!(!table.owned);
+    assert_eq!(
+        table.grants,
+        vec![GrantTrace {
+            privilege: "SELECT".to_string(),
+            held_by: "data-platform".to_string(),
+            inherited_from: Some("main.sales".to_string()),
+        }]
+    );
+
+    // denied, the trace shows what is held and what is missing
+    let explanation: AccessExplanation = permissions::explain_access(
+        api_client,
+        "engineer@example.com",
+        Privilege::Select,
+        SecurableType::Table,
+        "main.sales.orders",
+    )
+    .await
+    .unwrap();
+    assert!(!explanation.allowed);
+    assert_eq!(
+        explanation.verdict,
+        "denied: missing SELECT on main.sales.orders"
+    );
+
+    let trace: Value = serde_json::to_value(&explanation).unwrap();
+    assert_eq!(trace["principal"], "engineer@example.com");
+    assert_eq!(trace["privilege"], "SELECT");
+    assert_eq!(trace["groups"], json!(["account users"]));
+    assert_eq!(
+        trace["levels"][0],
+        json!({
+            "securable_type": "CATALOG",
+            "full_name": "main",
+            "owner": "admins",
+            "owned": false,
+            "grants": [{"privilege": "USE_CATALOG", "held_by": "engineer@example.com", "inherited_from": null}]
+        })
+    );
+    assert_eq!(
+        trace["checks"][1]["satisfied_by"],
+        "USE_SCHEMA granted to engineer@example.com on main.sales"
+    );
+    assert_eq!(
+        trace["missing"],
+        json!([{"privilege": "SELECT", "securable": "main.sales.orders", "satisfied_by": null}])
+    );
+}
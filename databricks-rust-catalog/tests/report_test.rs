@@ -0,0 +1,355 @@
+mod common;
+
+use axum::{
+    extract::{Path, Query, Request},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use databricks_rust_catalog::api::permissions::SecurableType;
+use databricks_rust_catalog::api::report::{
+    GrantRow, GrantsCrawler, GrantsReport, ReportFormat, SkippedObject,
+};
+use polars::prelude::{DataFrame, ParquetReader, SerReader};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const ANALYST: &str = "analyst@example.com";
+
+/// Counters shared with the stub.
+#[derive(Clone, Default)]
+struct Stub {
+    /// Fail the effective permissions of `main.sales.customers` while set.
+    fail: Arc<AtomicBool>,
+    /// Deny listing the schemas of `main` while set.
+    deny_schemas: Arc<AtomicBool>,
+    /// Deny listing the tables of `main.sales` while set.
+    deny_tables: Arc<AtomicBool>,
+    /// Number of effective permissions requests.
+    requests: Arc<AtomicUsize>,
+    /// Number of catalog, schema and table listing requests.
+    listings: Arc<AtomicUsize>,
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+/// Stub for the catalogs `main` and `dev`, the schema `main.sales` and its tables `orders` and
+/// `customers`, with one direct grant per object and inherited grants below `main`.
+fn metastore_stub(stub: Stub) -> Router {
+    let listings: Arc<AtomicUsize> = stub.listings.clone();
+    let count_listing = move |request: Request, next: Next| {
+        let listings: Arc<AtomicUsize> = listings.clone();
+        async move {
+            if !request.uri().path().contains("effective-permissions") {
+                listings.fetch_add(1, Ordering::SeqCst);
+            }
+            next.run(request).await
+        }
+    };
+    let deny_schemas: Arc<AtomicBool> = stub.deny_schemas.clone();
+    let deny_tables: Arc<AtomicBool> = stub.deny_tables.clone();
+    Router::new()
+        .route(
+            "/api/2.1/unity-catalog/catalogs",
+            get(|| async {
+                Json(json!({ "catalogs": [
+                    common::catalog_json("main", "admins"),
+                    common::catalog_json("dev", "")
+                ]}))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/schemas",
+            get(
+                move |Query(query): Query<HashMap<String, String>>| async move {
+                    let schemas: Value = match query["catalog_name"].as_str() {
+                        "main" if deny_schemas.load(Ordering::SeqCst) => {
+                            return (
+                                StatusCode::FORBIDDEN,
+                                Json(
+                                    json!({"error_code": "PERMISSION_DENIED", "message": "denied"}),
+                                ),
+                            )
+                                .into_response();
+                        }
+                        "main" => json!([common::schema_json("main", "sales", "admins")]),
+                        _ => json!([]),
+                    };
+                    Json(json!({ "schemas": schemas })).into_response()
+                },
+            ),
+        )
+        .route(
+            "/api/2.1/unity-catalog/tables",
+            get(
+                move |Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(query["schema_name"], "sales");
+                    if deny_tables.load(Ordering::SeqCst) {
+                        return (
+                            StatusCode::FORBIDDEN,
+                            Json(json!({"error_code": "PERMISSION_DENIED", "message": "denied"})),
+                        )
+                            .into_response();
+                    }
+                    Json(json!({ "tables": [
+                        common::table_json("main", "sales", "orders", "admins"),
+                        common::table_json("main", "sales", "customers", ANALYST)
+                    ]}))
+                    .into_response()
+                },
+            ),
+        )
+        .route(
+            "/api/2.1/unity-catalog/effective-permissions/:securable_type/:full_name",
+            get(
+                move |Path((_, full_name)): Path<(String, String)>| async move {
+                    stub.requests.fetch_add(1, Ordering::SeqCst);
+                    let in_flight: usize = stub.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    stub.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    stub.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                    if full_name == "main.sales.customers" && stub.fail.load(Ordering::SeqCst) {
+                        return (
+                            StatusCode::FORBIDDEN,
+                            Json(json!({"error_code": "PERMISSION_DENIED", "message": "denied"})),
+                        )
+                            .into_response();
+                    }
+                    let inherited = |name: &str| {
+                        json!({"principal": "data-engineers", "privileges": [
+                            {
+                                "privilege": "USE_CATALOG",
+                                "inherited_from_type": "CATALOG",
+                                "inherited_from_name": name
+                            }
+                        ]})
+                    };
+                    let assignments: Value = match full_name.as_str() {
+                        "main" => json!([
+                            {
+                                "principal": "data-engineers",
+                                "privileges": [{"privilege": "USE_CATALOG"}]
+                            }
+                        ]),
+                        "main.sales" => json!([
+                            inherited("main"),
+                            {
+                                "principal": "Finance, \"Ops\"",
+                                "privileges": [{"privilege": "USE_SCHEMA"}]
+                            }
+                        ]),
+                        "main.sales.orders" => json!([
+                            inherited("main"),
+                            {"principal": ANALYST, "privileges": [{"privilege": "SELECT"}]}
+                        ]),
+                        "main.sales.customers" => json!([inherited("main")]),
+                        _ => json!([]),
+                    };
+                    Json(json!({ "privilege_assignments": assignments })).into_response()
+                },
+            ),
+        )
+        .layer(middleware::from_fn(count_listing))
+}
+
+async fn crawler(stub: &Stub) -> GrantsCrawler {
+    let base_url: String = common::spawn_stub(metastore_stub(stub.clone())).await;
+    GrantsCrawler::new(common::api_client(&base_url))
+}
+
+/// Path in the temp directory, unique per test and removed if left over.
+fn temp_path(name: &str) -> PathBuf {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("grants-report-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn full_names(report: &GrantsReport) -> Vec<&str> {
+    let mut names: Vec<&str> = report.rows.iter().map(|r| r.full_name.as_str()).collect();
+    names.dedup();
+    names
+}
+
+#[tokio::test]
+async fn test_crawl_collects_owners_and_grants() {
+    let stub: Stub = Stub::default();
+    let report: GrantsReport = crawler(&stub).await.concurrency(2).crawl().await.unwrap();
+
+    // each catalog is followed by its schemas and their tables, `dev` has no owner or grants
+    assert_eq!(
+        full_names(&report),
+        vec![
+            "main",
+            "main.sales",
+            "main.sales.orders",
+            "main.sales.customers"
+        ]
+    );
+    assert_eq!(report.rows.len(), 10);
+    assert_eq!(
+        report.rows[3],
+        GrantRow {
+            securable_type: SecurableType::Schema,
+            full_name: "main.sales".to_string(),
+            principal: "data-engineers".to_string(),
+            privilege: "USE_CATALOG".to_string(),
+            inherited_from: Some("main".to_string()),
+        }
+    );
+    assert_eq!(report.rows[8].principal, ANALYST);
+    assert_eq!(report.rows[8].privilege, "OWNER");
+
+    assert_eq!(stub.requests.load(Ordering::SeqCst), 5);
+    assert!(stub.max_in_flight.load(Ordering::SeqCst) <= 2);
+}
+
+#[tokio::test]
+async fn test_export_formats() {
+    let stub: Stub = Stub::default();
+    let report: GrantsReport = crawler(&stub).await.crawl().await.unwrap();
+
+    let mut csv: Vec<u8> = Vec::new();
+    report.write_csv(&mut csv).unwrap();
+    let csv: String = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 11);
+    assert_eq!(
+        lines[0],
+        "securable_type,full_name,principal,privilege,inherited_from"
+    );
+    assert_eq!(lines[1], "CATALOG,main,admins,OWNER,");
+    assert_eq!(
+        lines[5],
+        "SCHEMA,main.sales,\"Finance, \"\"Ops\"\"\",USE_SCHEMA,"
+    );
+    assert_eq!(
+        lines[7],
+        "TABLE,main.sales.orders,data-engineers,USE_CATALOG,main"
+    );
+
+    let mut json: Vec<u8> = Vec::new();
+    report.write_json(&mut json).unwrap();
+    let rows: Vec<GrantRow> = serde_json::from_slice(&json).unwrap();
+    assert_eq!(rows, report.rows);
+    let json: Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json[0]["securable_type"], "CATALOG");
+    assert_eq!(json[0]["inherited_from"], Value::Null);
+
+    let path: PathBuf = temp_path("parquet");
+    report.write(&path, ReportFormat::Parquet).unwrap();
+    let parquet: Vec<u8> = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let df: DataFrame = ParquetReader::new(Cursor::new(parquet)).finish().unwrap();
+    assert_eq!(df.shape(), (10, 5));
+    assert_eq!(df.column("inherited_from").unwrap().null_count(), 7);
+
+    assert_eq!(
+        ReportFormat::from_path("grants.PARQUET"),
+        Some(ReportFormat::Parquet)
+    );
+    assert_eq!(ReportFormat::from_path("grants.txt"), None);
+}
+
+#[tokio::test]
+async fn test_crawl_resumes_from_checkpoint() {
+    let stub: Stub = Stub::default();
+    stub.fail.store(true, Ordering::SeqCst);
+    let checkpoint: PathBuf = temp_path("checkpoint");
+    let crawler: GrantsCrawler = crawler(&stub).await.concurrency(1).checkpoint(&checkpoint);
+
+    assert!(crawler.crawl().await.is_err());
+    let crawled: usize = std::fs::read_to_string(&checkpoint)
+        .unwrap()
+        .lines()
+        .count();
+    // the four listings and the three objects crawled before the failure
+    assert_eq!(crawled, 7);
+
+    // a line cut short by a crash is crawled again
+    let mut lines: String = std::fs::read_to_string(&checkpoint).unwrap();
+    lines.push_str("{\"securable_type\":\"TAB");
+    std::fs::write(&checkpoint, lines).unwrap();
+
+    // only the failed object and `dev` are fetched again, nothing is listed again
+    stub.fail.store(false, Ordering::SeqCst);
+    stub.requests.store(0, Ordering::SeqCst);
+    stub.listings.store(0, Ordering::SeqCst);
+    let report: GrantsReport = crawler.crawl().await.unwrap();
+    assert_eq!(stub.requests.load(Ordering::SeqCst), 2);
+    assert_eq!(stub.listings.load(Ordering::SeqCst), 0);
+    assert_eq!(report.rows.len(), 10);
+    assert_eq!(
+        full_names(&report),
+        vec![
+            "main",
+            "main.sales",
+            "main.sales.orders",
+            "main.sales.customers"
+        ]
+    );
+
+    // a complete checkpoint needs no requests
+    stub.requests.store(0, Ordering::SeqCst);
+    assert_eq!(crawler.crawl().await.unwrap(), report);
+    assert_eq!(stub.requests.load(Ordering::SeqCst), 0);
+    std::fs::remove_file(&checkpoint).unwrap();
+}
+
+#[tokio::test]
+async fn test_unreadable_schema_is_skipped() {
+    let stub: Stub = Stub::default();
+    stub.deny_tables.store(true, Ordering::SeqCst);
+    let checkpoint: PathBuf = temp_path("skipped");
+    let crawler: GrantsCrawler = crawler(&stub).await.checkpoint(&checkpoint);
+
+    // `main.sales` and its tables are left out, the rest of the crawl goes on
+    let report: GrantsReport = crawler.crawl().await.unwrap();
+    assert_eq!(full_names(&report), vec!["main"]);
+    assert_eq!(report.skipped.len(), 1);
+    let SkippedObject {
+        securable_type,
+        full_name,
+        reason,
+    } = &report.skipped[0];
+    assert_eq!(securable_type, &SecurableType::Schema);
+    assert_eq!(full_name, "main.sales");
+    assert!(reason.contains("PERMISSION_DENIED"), "{}", reason);
+    assert_eq!(stub.requests.load(Ordering::SeqCst), 2);
+
+    // the skipped schema is recorded in the checkpoint like any other listing
+    stub.listings.store(0, Ordering::SeqCst);
+    assert_eq!(crawler.crawl().await.unwrap(), report);
+    assert_eq!(stub.listings.load(Ordering::SeqCst), 0);
+    std::fs::remove_file(&checkpoint).unwrap();
+}
+
+#[tokio::test]
+async fn test_unreadable_catalog_is_skipped() {
+    let stub: Stub = Stub::default();
+    stub.deny_schemas.store(true, Ordering::SeqCst);
+    let checkpoint: PathBuf = temp_path("skipped-catalog");
+    let crawler: GrantsCrawler = crawler(&stub).await.checkpoint(&checkpoint);
+
+    // `main` and everything in it are left out, `dev` is still crawled
+    let report: GrantsReport = crawler.crawl().await.unwrap();
+    assert!(report.rows.is_empty());
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].securable_type, SecurableType::Catalog);
+    assert_eq!(report.skipped[0].full_name, "main");
+    assert!(report.skipped[0].reason.contains("PERMISSION_DENIED"));
+    assert_eq!(stub.requests.load(Ordering::SeqCst), 1);
+
+    stub.listings.store(0, Ordering::SeqCst);
+    assert_eq!(crawler.crawl().await.unwrap(), report);
+    assert_eq!(stub.listings.load(Ordering::SeqCst), 0);
+    std::fs::remove_file(&checkpoint).unwrap();
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::api::retry::RetryPolicy;
+use databricks_rust_catalog::Error;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stub that answers GET and POST with `status` for the first `failures` calls and 200 afterwards.
+async fn flaky_stub(
+    status: StatusCode,
+    failures: usize,
+    retry_after: Option<&'static str>,
+) -> (String, Arc<AtomicUsize>) {
+    let hits: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let handler = move || async move {
+        let hit: usize = counter.fetch_add(1, Ordering::SeqCst);
+        if hit < failures {
+            let mut headers: HeaderMap = HeaderMap::new();
+            if let Some(value) = retry_after {
+                headers.insert("retry-after", value.parse().unwrap());
+            }
+            (
+                status,
+                headers,
+                Json(json!({"error_code": "REQUEST_LIMIT_EXCEEDED", "message": "slow down"})),
+            )
+                .into_response()
+        } else {
+            Json(json!({"catalogs": []})).into_response()
+        }
+    };
+    let router: Router = Router::new().route(
+        "/api/2.1/unity-catalog/catalogs",
+        get(handler.clone()).post(handler),
+    );
+    let base_url: String = common::spawn_stub(router).await;
+    (format!("{}/api/2.1/unity-catalog/catalogs", base_url), hits)
+}
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy::default()
+        .with_base_delay(Duration::from_millis(5))
+        .with_jitter(0.0)
+}
+
+#[tokio::test]
+async fn test_retries_rate_limited_requests_until_success() {
+    let (url, hits) = flaky_stub(StatusCode::TOO_MANY_REQUESTS, 2, Some("0")).await;
+    let client = APIClient::new("localhost".to_string(), "token".to_string())
+        .with_retry_policy(fast_policy());
+
+    let response = client.fetch(&url, None).await.unwrap();
+
+    assert!(response.status().is_success());
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let (url, hits) = flaky_stub(StatusCode::SERVICE_UNAVAILABLE, 10, None).await;
+    let client = APIClient::new("localhost".to_string(), "token".to_string())
+        .with_retry_policy(fast_policy().with_max_attempts(3));
+
+    let err: Error = client.fetch(&url, None).await.unwrap_err();
+
+    assert!(matches!(err, Error::Api(ref e) if e.status == 503));
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_does_not_retry_non_retryable_status() {
+    let (url, hits) = flaky_stub(StatusCode::FORBIDDEN, 10, None).await;
+    let client = APIClient::new("localhost".to_string(), "token".to_string())
+        .with_retry_policy(fast_policy());
+
+    let err: Error = client.fetch(&url, None).await.unwrap_err();
+
+    assert!(matches!(err, Error::Api(ref e) if e.status == 403));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_writes_are_only_retried_when_enabled() {
+    let (url, hits) = flaky_stub(StatusCode::SERVICE_UNAVAILABLE, 2, None).await;
+    let client = APIClient::new("localhost".to_string(), "token".to_string())
+        .with_retry_policy(fast_policy());
+
+    let result: Result<serde_json::Value, Error> = client.post(&url, &json!({})).await;
+
+    assert!(matches!(result, Err(Error::Api(ref e)) if e.status == 503));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    let client = client.with_retry_policy(fast_policy().with_retry_writes(true));
+    let _: serde_json::Value = client.post(&url, &json!({})).await.unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_retry_after_header_overrides_backoff() {
+    let policy: RetryPolicy = RetryPolicy::default().with_max_delay(Duration::from_secs(5));
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("retry-after", "2".parse().unwrap());
+    assert_eq!(policy.delay(1, Some(&headers)), Duration::from_secs(2));
+
+    headers.insert("retry-after", "120".parse().unwrap());
+    assert_eq!(policy.delay(1, Some(&headers)), Duration::from_secs(5));
+}
+
+#[test]
+fn test_backoff_grows_exponentially() {
+    let policy: RetryPolicy = RetryPolicy::default()
+        .with_base_delay(Duration::from_millis(100))
+        .with_jitter(0.0);
+    assert_eq!(policy.delay(1, None), Duration::from_millis(100));
+    assert_eq!(policy.delay(2, None), Duration::from_millis(200));
+    assert_eq!(policy.delay(3, None), Duration::from_millis(400));
+}
+
+#[test]
+fn test_out_of_range_jitter_is_clamped() {
+    let policy: RetryPolicy = RetryPolicy {
+        base_delay: Duration::from_millis(100),
+        jitter: 1.5,
+        ..RetryPolicy::default()
+    };
+    for _ in 0..100 {
+        assert!(policy.delay(1, None) <= Duration::from_millis(200));
+    }
+
+    let policy: RetryPolicy = RetryPolicy {
+        base_delay: Duration::from_millis(100),
+        jitter: f64::NAN,
+        ..RetryPolicy::default()
+    };
+    assert_eq!(policy.delay(1, None), Duration::from_millis(100));
+}
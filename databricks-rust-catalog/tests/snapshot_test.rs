@@ -0,0 +1,220 @@
+mod common;
+
+use axum::{
+    extract::{Path, Query},
+    routing::get,
+    Json, Router,
+};
+use databricks_rust_catalog::api::api_client::APIClient;
+use databricks_rust_catalog::api::evaluator::AccessExplanation;
+use databricks_rust_catalog::api::grants::{ObjectPlan, Plan};
+use databricks_rust_catalog::api::permissions::{PermissionsChange, Privilege, SecurableType};
+use databricks_rust_catalog::api::snapshot::{self, GrantsSnapshot};
+use databricks_rust_catalog::Error;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+const MEMBER: &str = "member@example.com";
+const ANALYST: &str = "analyst@example.com";
+
+/// Stub for the catalog `main` with schema `sales` and tables `orders` and `customers`,
+/// their direct grants, and SCIM users and groups:
+/// `member@example.com` -> `data-engineers` -> `data-platform`.
+fn workspace_stub() -> Router {
+    Router::new()
+        .route(
+            "/api/2.0/preview/scim/v2/Me",
+            get(|| async { Json(common::user_json("token-user@example.com")) }),
+        )
+        .route(
+            "/api/2.0/preview/scim/v2/Users",
+            get(|Query(query): Query<HashMap<String, String>>| async move {
+                let mut user: Value = common::user_json(MEMBER);
+                user["groups"] = json!([{"value": "g1"}]);
+                assert!(query["filter"].contains(MEMBER));
+                Json(json!({ "Resources": [user] }))
+            }),
+        )
+        .route(
+            "/api/2.0/preview/scim/v2/Groups/:id",
+            get(|Path(id): Path<String>| async move {
+                let (display_name, parents): (&str, Value) = match id.as_str() {
+                    "g1" => ("data-engineers", json!([{"value": "g2"}])),
+                    _ => ("data-platform", json!([])),
+                };
+                Json(json!({ "id": id, "displayName": display_name, "groups": parents }))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/catalogs/:name",
+            get(|Path(name): Path<String>| async move {
+                Json(common::catalog_json(&name, "admins"))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/schemas",
+            get(|| async {
+                Json(json!({ "schemas": [common::schema_json("main", "sales", "admins")] }))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/tables",
+            get(|| async {
+                Json(json!({ "tables": [
+                    common::table_json("main", "sales", "orders", "admins"),
+                    common::table_json("main", "sales", "customers", ANALYST)
+                ]}))
+            }),
+        )
+        .route(
+            "/api/2.1/unity-catalog/permissions/:securable_type/:full_name",
+            get(|Path((_, full_name)): Path<(String, String)>| async move {
+                let assignments: Value = match full_name.as_str() {
+                    "main" => json!([
+                        {"principal": "data-engineers", "privileges": ["USE_CATALOG"]},
+                        {"principal": ANALYST, "privileges": ["USE_CATALOG"]}
+                    ]),
+                    "main.sales" => json!([
+                        {"principal": "data-platform", "privileges": ["USE_SCHEMA", "SELECT"]}
+                    ]),
+                    _ => json!([]),
+                };
+                Json(json!({ "privilege_assignments": assignments }))
+            }),
+        )
+}
+
+async fn take_snapshot() -> GrantsSnapshot {
+    let base_url: String = common::spawn_stub(workspace_stub()).await;
+    let api_client: APIClient = common::api_client(&base_url);
+    snapshot::take_snapshot(api_client, "main", &[MEMBER])
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_snapshot_and_evaluate_offline() {
+    let snapshot: GrantsSnapshot = take_snapshot().await;
+
+    let objects: Vec<&str> = snapshot
+        .objects
+        .iter()
+        .map(|o| o.full_name.as_str())
+        .collect();
+    assert_eq!(
+        objects,
+        vec![
+            "main",
+            "main.sales",
+            "main.sales.orders",
+            "main.sales.customers"
+        ]
+    );
+    assert_eq!(
+        snapshot.memberships[MEMBER],
+        BTreeSet::from(["data-engineers".to_string()])
+    );
+    assert_eq!(
+        snapshot.identity(MEMBER).groups,
+        vec!["data-engineers", "data-platform", "account users"]
+    );
+
+    // the file round trips
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("grants-snapshot-{}.json", std::process::id()));
+    snapshot.save(&path).unwrap();
+    let snapshot: GrantsSnapshot = GrantsSnapshot::load(&path).unwrap();
+    std::fs::write(&path, "[]").unwrap();
+    assert!(matches!(
+        GrantsSnapshot::load(&path),
+        Err(Error::Snapshot(msg)) if msg.starts_with("unable to parse")
+    ));
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        GrantsSnapshot::load(&path),
+        Err(Error::Snapshot(msg)) if msg.starts_with("unable to read")
+    ));
+
+    // SELECT on the schema is inherited by its tables, USE_* come from nested groups
+    assert!(snapshot.can_read("main.sales.orders", MEMBER).unwrap());
+    assert!(!snapshot.can_write("main.sales.orders", MEMBER).unwrap());
+    // owning a table does not grant USE_SCHEMA
+    assert!(!snapshot.can_read("main.sales.customers", ANALYST).unwrap());
+
+    match snapshot.can_read("main.sales.returns", MEMBER) {
+        Err(Error::Snapshot(msg)) => {
+            assert_eq!(msg, "table main.sales.returns is not in the snapshot")
+        }
+        other => panic!("expected a snapshot error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_what_if() {
+    let snapshot: GrantsSnapshot = take_snapshot().await;
+    assert!(!snapshot.can_read("main.sales.orders", ANALYST).unwrap());
+
+    // what if the analyst joins data-platform
+    let mut what_if: GrantsSnapshot = snapshot.clone();
+    what_if.add_member(ANALYST, "data-platform");
+    assert!(what_if.can_read("main.sales.orders", ANALYST).unwrap());
+    assert!(what_if.can_read("main.sales.customers", ANALYST).unwrap());
+    what_if.remove_member(ANALYST, "data-platform");
+    assert!(!what_if.can_read("main.sales.orders", ANALYST).unwrap());
+
+    // what if data-platform loses USE_SCHEMA, principals match regardless of case
+    let mut what_if: GrantsSnapshot = snapshot.clone();
+    what_if
+        .revoke(
+            SecurableType::Schema,
+            "main.sales",
+            "Data-Platform",
+            &[Privilege::UseSchema],
+        )
+        .unwrap();
+    let explanation: AccessExplanation = what_if
+        .explain(
+            MEMBER,
+            Privilege::Select,
+            SecurableType::Table,
+            "main.sales.orders",
+        )
+        .unwrap();
+    assert!(!explanation.allowed);
+    assert_eq!(
+        explanation.verdict,
+        "denied: missing USE_SCHEMA on main.sales"
+    );
+
+    // what if a grants plan is applied
+    let mut what_if: GrantsSnapshot = snapshot.clone();
+    let plan: Plan = Plan {
+        objects: vec![
+            ObjectPlan {
+                securable_type: SecurableType::Schema,
+                full_name: "main.sales".to_string(),
+                changes: vec![PermissionsChange::new(ANALYST).grant(&[Privilege::UseSchema])],
+            },
+            ObjectPlan {
+                securable_type: SecurableType::Table,
+                full_name: "main.sales.orders".to_string(),
+                changes: vec![PermissionsChange::new(ANALYST).grant(&[Privilege::Modify])],
+            },
+        ],
+    };
+    what_if.apply_plan(&plan).unwrap();
+    assert!(what_if.can_write("main.sales.orders", ANALYST).unwrap());
+    // ownership now counts once USE_SCHEMA is held
+    assert!(what_if.can_read("main.sales.customers", ANALYST).unwrap());
+
+    // the original snapshot is unchanged
+    assert!(!snapshot
+        .check(
+            ANALYST,
+            Privilege::UseSchema,
+            SecurableType::Schema,
+            "main.sales"
+        )
+        .unwrap());
+}
@@ -2,7 +2,6 @@
 use axum::http::StatusCode;
 use axum::{response::IntoResponse, routing::get, Json, Router};
 use dotenv::dotenv;
-use log;
 use magic_crypt::{new_magic_crypt, MagicCryptTrait};
 use serde_json::json;
 use std::env;
@@ -76,6 +75,5 @@ async fn get_storage_credentials(
 
 fn encrypt_string(plain_text: String, encrypt_key: &str) -> String {
     let mc = new_magic_crypt!(encrypt_key, 256);
-    let encrypted_text = mc.encrypt_str_to_base64(plain_text);
-    encrypted_text
+    mc.encrypt_str_to_base64(plain_text)
 }